@@ -2,7 +2,12 @@ use copy_to_output::copy_to_output;
 use std::env;
 
 fn main() {
-    println!("cargo:rerun-if-changed=inputs/*");
+    // Cargo does not expand globs here, but watching the directory covers every file in it
+    println!("cargo:rerun-if-changed=inputs");
+    // Rerunning the build script also rebuilds the crate, which picks up new problem and example
+    // files
+    println!("cargo:rerun-if-changed=examples");
+    println!("cargo:rerun-if-changed=src/aoc/problems");
     copy_to_output("inputs", &env::var("PROFILE").unwrap())
         .expect("Could not copy inputs to target directory");
 }
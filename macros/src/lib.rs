@@ -1,48 +1,797 @@
+use std::path::{Path, PathBuf};
+
 use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
 use syn::{
     parse::{Parse, ParseStream},
-    parse_macro_input, LitStr,
+    parse_macro_input, Expr, ImplItem, Item, LitStr, Token,
 };
 
 struct MacroInput {
     problem_dir: LitStr,
+    example_dir: Option<LitStr>,
 }
 
 impl Parse for MacroInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let problem_dir = input.parse()?;
+        let example_dir = if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
         Ok(MacroInput {
-            problem_dir: input.parse()?,
+            problem_dir,
+            example_dir,
         })
     }
 }
 
+/// A problem module discovered in the problem directory
+struct ProblemFile {
+    day: u8,
+    module: String,
+    has_part2: bool,
+}
+
+/// An example input for a day, found in the example directory
+struct ExampleFile {
+    day: u8,
+    name: String,
+    path: String,
+    has_answers: bool,
+}
+
+/// Determine whether the header of an example file gives an answer for either part
+///
+/// A freshly scaffolded example has its answers commented out, as in `# part1 = `, until they are
+/// filled in. Its test is generated as ignored so that it does not fail in the meantime.
+fn has_answers(contents: &str) -> bool {
+    contents
+        .lines()
+        .map(str::trim)
+        .take_while(|line| *line != "---")
+        .any(|line| line.starts_with("part1") || line.starts_with("part2"))
+}
+
+/// Parse the day number out of a name of the form `dayNN` followed by a suffix
+fn parse_day(name: &str, suffix: &str) -> Option<u8> {
+    let digits = name.strip_prefix("day")?.strip_suffix(suffix)?;
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits
+        .parse::<u8>()
+        .ok()
+        .filter(|day| (1..=25).contains(day))
+}
+
+/// Determine whether a problem file solves part 2
+///
+/// A day whose part 2 has not been solved yet declares it as `Unsolved` in its `SOLUTIONS`
+/// constant, e.g. `(&SolutionPart1, &Unsolved)`. A day built on a `Solver` instead leaves
+/// `part2` out of its `impl Solver`.
+fn has_part2(file: &syn::File) -> Option<bool> {
+    let solutions = file.items.iter().find_map(|item| match item {
+        Item::Const(item) if item.ident == "SOLUTIONS" => Some(&item.expr),
+        _ => None,
+    })?;
+
+    let part2 = match solutions.as_ref() {
+        Expr::Tuple(tuple) if tuple.elems.len() == 2 => &tuple.elems[1],
+        Expr::Path(_) => return Some(solver_has_part2(file)),
+        _ => return Some(true),
+    };
+    let path = match part2 {
+        Expr::Reference(reference) => match reference.expr.as_ref() {
+            Expr::Path(path) => &path.path,
+            _ => return Some(true),
+        },
+        _ => return Some(true),
+    };
+    Some(
+        path.segments
+            .last()
+            .is_none_or(|segment| segment.ident != "Unsolved"),
+    )
+}
+
+/// Determine whether the `impl Solver` in a problem file defines `part2`, assuming it does if
+/// there is no such impl
+///
+/// The impl is recognized by its `part1` function, which plain `Aoc` impls do not have.
+fn solver_has_part2(file: &syn::File) -> bool {
+    let defines = |item: &syn::ItemImpl, name: &str| {
+        item.items.iter().any(|item| match item {
+            ImplItem::Fn(function) => function.sig.ident == name,
+            _ => false,
+        })
+    };
+    file.items
+        .iter()
+        .find_map(|item| match item {
+            Item::Impl(item) if item.trait_.is_some() && defines(item, "part1") => Some(item),
+            _ => None,
+        })
+        .is_none_or(|item| defines(item, "part2"))
+}
+
+/// Resolve a directory named in the macro input against the root of the crate that invokes it, so
+/// that the build finds it whatever the working directory is
+fn crate_path(dir: &str) -> PathBuf {
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    Path::new(&root).join(dir)
+}
+
+fn read_problem_files(problem_dir: &Path, span: Span) -> syn::Result<Vec<ProblemFile>> {
+    let error = |message: String| syn::Error::new(span, message);
+    let read_error = |err: std::io::Error| {
+        error(format!(
+            "Could not read '{}': {}",
+            problem_dir.display(),
+            err
+        ))
+    };
+
+    let mut problems = Vec::new();
+    for entry in std::fs::read_dir(problem_dir).map_err(read_error)? {
+        let entry = entry.map_err(read_error)?;
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let day = parse_day(&file_name, ".rs").ok_or_else(|| {
+            error(format!(
+                "Problem file '{}' must be named dayNN.rs with NN between 01 and 25",
+                file_name
+            ))
+        })?;
+
+        let source = std::fs::read_to_string(entry.path())
+            .map_err(|err| error(format!("Could not read '{}': {}", file_name, err)))?;
+        let file = syn::parse_file(&source)
+            .map_err(|err| error(format!("Could not parse '{}': {}", file_name, err)))?;
+        let has_part2 = has_part2(&file).ok_or_else(|| {
+            error(format!(
+                "Problem file '{}' does not define SOLUTIONS",
+                file_name
+            ))
+        })?;
+
+        problems.push(ProblemFile {
+            day,
+            module: file_name.trim_end_matches(".rs").to_string(),
+            has_part2,
+        });
+    }
+
+    problems.sort_by_key(|problem| problem.day);
+    for pair in problems.windows(2) {
+        if pair[0].day == pair[1].day {
+            return Err(error(format!(
+                "Problem files '{}.rs' and '{}.rs' are both for day {}",
+                pair[0].module, pair[1].module, pair[0].day
+            )));
+        }
+    }
+
+    Ok(problems)
+}
+
+fn read_example_files(
+    example_dir: &Path,
+    problems: &[ProblemFile],
+    span: Span,
+) -> syn::Result<Vec<ExampleFile>> {
+    let error = |message: String| syn::Error::new(span, message);
+    let read_dir = |dir: &Path| {
+        std::fs::read_dir(dir)
+            .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+            .map_err(|err| error(format!("Could not read '{}': {}", dir.display(), err)))
+    };
+
+    let mut examples = Vec::new();
+    for day_entry in read_dir(example_dir)? {
+        let dir_name = day_entry.file_name().to_string_lossy().into_owned();
+        let day = parse_day(&dir_name, "").ok_or_else(|| {
+            error(format!(
+                "Example directory '{}' must be named dayNN with NN between 01 and 25",
+                dir_name
+            ))
+        })?;
+        if !problems.iter().any(|problem| problem.day == day) {
+            return Err(error(format!(
+                "Example directory '{}' is for a day with no problem file",
+                dir_name
+            )));
+        }
+
+        for entry in read_dir(&day_entry.path())? {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let stem = file_name.strip_suffix(".txt").ok_or_else(|| {
+                error(format!(
+                    "Example file '{}/{}' must have a .txt extension",
+                    dir_name, file_name
+                ))
+            })?;
+            let name = stem
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() {
+                        c.to_ascii_lowercase()
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>();
+            let contents = std::fs::read_to_string(entry.path()).map_err(|err| {
+                error(format!(
+                    "Could not read '{}/{}': {}",
+                    dir_name, file_name, err
+                ))
+            })?;
+            examples.push(ExampleFile {
+                day,
+                name: format!("{}_{}", dir_name, name),
+                path: entry.path().to_string_lossy().into_owned(),
+                has_answers: has_answers(&contents),
+            });
+        }
+    }
+
+    examples.sort_by(|a, b| a.name.cmp(&b.name));
+    for pair in examples.windows(2) {
+        if pair[0].name == pair[1].name {
+            return Err(error(format!(
+                "Example files '{}' and '{}' have the same test name",
+                pair[0].path, pair[1].path
+            )));
+        }
+    }
+
+    Ok(examples)
+}
+
+/// Declare a module for every `dayNN.rs` file in a directory and generate a lookup by day
+///
+/// The generated code provides `PROBLEMS`, the metadata of every day sorted by day number, and
+/// `problem(day)`, which returns the solutions for a day.
+///
+/// If a second directory is given, every `dayNN/*.txt` file in it becomes a test that checks the
+/// day's solutions against the answers in the file's header. A file whose header gives no answers
+/// yet becomes an ignored test.
 #[proc_macro]
 pub fn define_problems(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as MacroInput);
     let problem_dir = input.problem_dir.value();
-    let mut output = String::new();
-    output.push_str("use super::Solutions;\n\n");
-    let problem_files = std::fs::read_dir(problem_dir)
-        .unwrap()
-        .map(|file| {
-            file.unwrap()
-                .file_name()
-                .to_str()
-                .unwrap()
-                .trim_end_matches(".rs")
-                .to_string()
-        })
+
+    let problems = match read_problem_files(&crate_path(&problem_dir), input.problem_dir.span()) {
+        Ok(problems) => problems,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let examples = match &input.example_dir {
+        Some(example_dir) => {
+            // Example paths are absolute so that `include_str!` finds them from the generated code
+            let dir = crate_path(&example_dir.value());
+            match read_example_files(&dir, &problems, example_dir.span()) {
+                Ok(examples) => examples,
+                Err(err) => return err.to_compile_error().into(),
+            }
+        }
+        None => Vec::new(),
+    };
+
+    let modules = problems
+        .iter()
+        .map(|problem| format_ident!("{}", problem.module))
         .collect::<Vec<_>>();
-    for problem_file in problem_files.iter() {
-        output.push_str(&format!("mod {};\n", problem_file));
-    }
-    output.push_str("\n");
-    output.push_str("pub fn all_problems() -> Vec<Solutions> {\n");
-    output.push_str("    vec![\n");
-    for problem_file in problem_files.iter() {
-        output.push_str(&format!("        {}::SOLUTIONS,\n", problem_file));
-    }
-    output.push_str("    ]\n");
-    output.push_str("}\n");
-    output.parse().unwrap()
+    let infos = problems.iter().map(|problem| {
+        let ProblemFile {
+            day,
+            module,
+            has_part2,
+        } = problem;
+        quote! {
+            ProblemInfo { day: #day, module: #module, has_part2: #has_part2 }
+        }
+    });
+    let days = problems.iter().map(|problem| problem.day);
+    let tests = examples.iter().map(|example| {
+        let ExampleFile {
+            day,
+            name,
+            path,
+            has_answers,
+        } = example;
+        let test = format_ident!("{}", name);
+        let ignore = (!has_answers).then(|| quote! { #[ignore = "no expected answers"] });
+        quote! {
+            #[test]
+            #ignore
+            fn #test() {
+                super::super::examples::check(#day, #name, include_str!(#path));
+            }
+        }
+    });
+
+    quote! {
+        use super::{ProblemInfo, Solutions};
+
+        #(mod #modules;)*
+
+        /// Metadata for every problem, sorted by day
+        pub const PROBLEMS: &[ProblemInfo] = &[#(#infos),*];
+
+        /// Return the solutions for a day, or None if the day has not been solved
+        pub fn problem(day: u8) -> Option<Solutions> {
+            match day {
+                #(#days => Some(#modules::SOLUTIONS),)*
+                _ => None,
+            }
+        }
+
+        #[cfg(test)]
+        mod example_tests {
+            #(#tests)*
+        }
+    }
+    .into()
+}
+
+/// One piece of a `#[scan("...")]` format
+enum FormatItem {
+    /// Literal text, matched word by word
+    Literal(String),
+    /// A field scanned with its own `Scan` implementation
+    Field(String),
+    /// A `Vec` field scanned as a list with the given separator
+    List(String, String),
+}
+
+/// Split a `#[scan("...")]` format into literal text and fields
+fn parse_format(format: &str) -> Result<Vec<FormatItem>, String> {
+    let mut items = Vec::new();
+    let mut literal = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err("unclosed '{' in format".to_string()),
+                    }
+                }
+                if !literal.is_empty() {
+                    items.push(FormatItem::Literal(std::mem::take(&mut literal)));
+                }
+                items.push(parse_placeholder(&placeholder)?);
+            }
+            '}' => return Err("unmatched '}' in format, use '}}' for a literal brace".to_string()),
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        items.push(FormatItem::Literal(literal));
+    }
+    Ok(items)
+}
+
+/// Parse the inside of a `{...}` placeholder: a field name, optionally followed by `: sep('x')`
+fn parse_placeholder(placeholder: &str) -> Result<FormatItem, String> {
+    let (name, modifier) = match placeholder.split_once(':') {
+        Some((name, modifier)) => (name.trim(), Some(modifier.trim())),
+        None => (placeholder.trim(), None),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(format!("'{{{}}}' does not name a field", placeholder));
+    }
+    let Some(modifier) = modifier else {
+        return Ok(FormatItem::Field(name.to_string()));
+    };
+
+    let separator = modifier
+        .strip_prefix("sep(")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .map(str::trim)
+        .and_then(|quoted| {
+            quoted
+                .strip_prefix('\'')
+                .and_then(|rest| rest.strip_suffix('\''))
+                .or_else(|| {
+                    quoted
+                        .strip_prefix('"')
+                        .and_then(|rest| rest.strip_suffix('"'))
+                })
+        })
+        .filter(|separator| !separator.is_empty())
+        .ok_or_else(|| format!("expected sep('x') after '{}:', found '{}'", name, modifier))?;
+    Ok(FormatItem::List(name.to_string(), separator.to_string()))
+}
+
+/// Derive `Scan` for a struct from a `#[scan("...")]` format that names each of its fields once
+///
+/// An enum gives a format for each variant instead, and is scanned as the first variant whose
+/// format matches. See the `Scan` trait for the format syntax.
+#[proc_macro_derive(Scan, attributes(scan))]
+pub fn derive_scan(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    match scan_impl(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Return the `#[scan("...")]` format among some attributes
+fn scan_format(attrs: &[syn::Attribute], ident: &syn::Ident) -> syn::Result<LitStr> {
+    attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("scan"))
+        .ok_or_else(|| syn::Error::new_spanned(ident, "missing #[scan(\"...\")] format"))?
+        .parse_args::<LitStr>()
+}
+
+/// Return the names of the fields of a struct or variant, which must be named unless there are none
+fn field_names(fields: &syn::Fields, ident: &syn::Ident) -> syn::Result<Vec<syn::Ident>> {
+    match fields {
+        syn::Fields::Named(fields) => Ok(fields
+            .named
+            .iter()
+            .filter_map(|field| field.ident.clone())
+            .collect()),
+        syn::Fields::Unit => Ok(Vec::new()),
+        syn::Fields::Unnamed(_) => Err(syn::Error::new_spanned(
+            ident,
+            "Scan can only be derived for named fields",
+        )),
+    }
+}
+
+/// Generate the steps that scan literal format text
+///
+/// Each word must come next in the input, and each run of whitespace between words must match at
+/// least one space or tab, so that `"a b"` does not accept `ab`.
+fn literal_steps(text: &str) -> Vec<proc_macro2::TokenStream> {
+    let mut steps = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let trimmed = rest.trim_start();
+        if trimmed.len() < rest.len() {
+            steps.push(quote! {
+                if !scanner.remaining().starts_with([' ', '\t']) {
+                    return Err(scanner.error("whitespace"));
+                }
+                scanner.skip_spaces();
+            });
+            rest = trimmed;
+            continue;
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let word = &rest[..end];
+        steps.push(quote! {
+            scanner.scan_string(#word)?;
+        });
+        rest = &rest[end..];
+    }
+    steps
+}
+
+/// Generate the statements that scan each field of a format into a variable of the same name
+fn scan_steps(
+    fields: &[syn::Ident],
+    format: &LitStr,
+    owner: &syn::Ident,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let items = parse_format(&format.value()).map_err(|err| syn::Error::new(format.span(), err))?;
+
+    let mut seen = Vec::new();
+    let mut steps = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let name = match item {
+            FormatItem::Literal(text) => {
+                steps.extend(literal_steps(text));
+                continue;
+            }
+            FormatItem::Field(name) | FormatItem::List(name, _) => name,
+        };
+        let Some(field) = fields.iter().find(|field| *field == name) else {
+            return Err(syn::Error::new(
+                format.span(),
+                format!("'{}' is not a field of {}", name, owner),
+            ));
+        };
+        if seen.contains(&field) {
+            return Err(syn::Error::new(
+                format.span(),
+                format!("field '{}' appears more than once in the format", name),
+            ));
+        }
+        seen.push(field);
+
+        steps.push(match item {
+            FormatItem::List(_, separator) if separator.trim().is_empty() => {
+                // A list followed by literal text ends where that text starts, so an item that
+                // fails anywhere else is reported rather than silently ending the list
+                let on_error = match items.get(index + 1) {
+                    Some(FormatItem::Literal(text)) => match text.split_whitespace().next() {
+                        Some(word) => quote! {
+                            if !scanner.remaining().starts_with(#word) {
+                                return Err(err);
+                            }
+                        },
+                        None => quote! {},
+                    },
+                    _ => quote! {},
+                };
+                // The spaces after the last item are left for the whitespace that follows the list
+                // in the format
+                quote! {
+                    let mut #field = ::std::vec::Vec::new();
+                    loop {
+                        let before = scanner.checkpoint();
+                        scanner.skip_spaces();
+                        if scanner.at_line_end() {
+                            scanner.restore(before);
+                            break;
+                        }
+                        let start = scanner.checkpoint();
+                        match crate::util::scanner::Scan::scan(scanner) {
+                            Ok(item) => #field.push(item),
+                            Err(err) => {
+                                scanner.restore(start);
+                                #on_error
+                                scanner.restore(before);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            FormatItem::List(_, separator) => quote! {
+                scanner.skip_spaces();
+                let mut #field = vec![crate::util::scanner::Scan::scan(scanner)?];
+                loop {
+                    let before = scanner.checkpoint();
+                    scanner.skip_spaces();
+                    if scanner.at_line_end() || !scanner.remaining().starts_with(#separator) {
+                        scanner.restore(before);
+                        break;
+                    }
+                    scanner.scan_string(#separator)?;
+                    scanner.skip_spaces();
+                    #field.push(crate::util::scanner::Scan::scan(scanner)?);
+                }
+            },
+            // Only a field that starts the format skips spaces, such as the indentation of a line
+            _ if index == 0 => quote! {
+                scanner.skip_spaces();
+                let #field = crate::util::scanner::Scan::scan(scanner)?;
+            },
+            _ => quote! {
+                let #field = crate::util::scanner::Scan::scan(scanner)?;
+            },
+        });
+    }
+    if let Some(missing) = fields.iter().find(|field| !seen.contains(field)) {
+        return Err(syn::Error::new(
+            format.span(),
+            format!("field '{}' does not appear in the format", missing),
+        ));
+    }
+    Ok(steps)
+}
+
+fn scan_impl(input: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let body = match &input.data {
+        syn::Data::Struct(data) => {
+            let fields = field_names(&data.fields, name)?;
+            let steps = scan_steps(&fields, &scan_format(&input.attrs, name)?, name)?;
+            quote! {
+                #(#steps)*
+                Ok(#name { #(#fields),* })
+            }
+        }
+        syn::Data::Enum(data) if !data.variants.is_empty() => {
+            let variants = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let fields = field_names(&variant.fields, ident)?;
+                    let steps = scan_steps(&fields, &scan_format(&variant.attrs, ident)?, ident)?;
+                    Ok(quote! {
+                        // Each variant is tried from the same place, keeping the error that got
+                        // furthest in case none of them matches
+                        let attempt = (|| -> ::std::result::Result<
+                            Self,
+                            crate::util::scanner::ScanError,
+                        > {
+                            #(#steps)*
+                            Ok(#name::#ident { #(#fields),* })
+                        })();
+                        match attempt {
+                            Ok(value) => return Ok(value),
+                            Err(err) => {
+                                scanner.restore(start);
+                                if furthest.as_ref().is_none_or(|f| err.offset() > f.offset()) {
+                                    furthest = Some(err);
+                                }
+                            }
+                        }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                let start = scanner.checkpoint();
+                let mut furthest: ::std::option::Option<crate::util::scanner::ScanError> = None;
+                #(#variants)*
+                Err(furthest.unwrap())
+            }
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "Scan can only be derived for structs and enums with variants",
+            ))
+        }
+    };
+
+    let mut generics = input.generics.clone();
+    generics.params.insert(0, syn::parse_quote!('scan));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics crate::util::scanner::Scan<'scan> for #name #ty_generics #where_clause {
+            fn scan(
+                scanner: &mut crate::util::scanner::Scanner<'scan>,
+            ) -> ::std::result::Result<Self, crate::util::scanner::ScanError> {
+                #body
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty directory for a test, holding the given files
+    fn temp_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc-macros-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in files {
+            std::fs::write(dir.join(name), contents).unwrap();
+        }
+        dir
+    }
+
+    fn part2(source: &str) -> Option<bool> {
+        has_part2(&syn::parse_file(source).unwrap())
+    }
+
+    const SOLVED: &str = "pub const SOLUTIONS: Solutions = (&SolutionPart1, &SolutionPart2);";
+
+    #[test]
+    fn day_names() {
+        assert_eq!(parse_day("day01.rs", ".rs"), Some(1));
+        assert_eq!(parse_day("day7.rs", ".rs"), Some(7));
+        assert_eq!(parse_day("day25", ""), Some(25));
+        assert_eq!(parse_day("day00.rs", ".rs"), None);
+        assert_eq!(parse_day("day26.rs", ".rs"), None);
+        assert_eq!(parse_day("day+1.rs", ".rs"), None);
+        assert_eq!(parse_day("day.rs", ".rs"), None);
+        assert_eq!(parse_day("day01.txt", ".rs"), None);
+        assert_eq!(parse_day("mod.rs", ".rs"), None);
+    }
+
+    #[test]
+    fn part2_from_solutions() {
+        assert_eq!(part2(SOLVED), Some(true));
+        assert_eq!(
+            part2("pub const SOLUTIONS: Solutions = (&SolutionPart1, &Unsolved);"),
+            Some(false)
+        );
+        assert_eq!(
+            part2("pub const SOLUTIONS: Solutions = (&SolutionPart1, &crate::aoc::Unsolved);"),
+            Some(false)
+        );
+        assert_eq!(part2("const OTHER: u8 = 1;"), None);
+    }
+
+    #[test]
+    fn part2_from_solver() {
+        let solver = |functions: &str| {
+            format!(
+                "pub const SOLUTIONS: Solutions = Day::SOLUTIONS;
+                 impl Display for Day {{ fn fmt() {{}} }}
+                 impl Solver for Day {{ {} }}",
+                functions
+            )
+        };
+        assert_eq!(part2(&solver("fn parse() {} fn part1() {}")), Some(false));
+        assert_eq!(
+            part2(&solver("fn parse() {} fn part1() {} fn part2() {}")),
+            Some(true)
+        );
+        // Without a recognizable impl, part 2 is assumed to be solved
+        assert_eq!(
+            part2("pub const SOLUTIONS: Solutions = Day::SOLUTIONS;"),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn problem_files_sorted_by_day() {
+        let unsolved = "pub const SOLUTIONS: Solutions = (&SolutionPart1, &Unsolved);";
+        let dir = temp_dir(
+            "sorted",
+            &[
+                ("day10.rs", SOLVED),
+                ("day3.rs", unsolved),
+                ("day01.rs", SOLVED),
+            ],
+        );
+        let problems = read_problem_files(&dir, Span::call_site()).unwrap();
+        let summary = problems
+            .iter()
+            .map(|problem| (problem.day, problem.module.as_str(), problem.has_part2))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![(1, "day01", true), (3, "day3", false), (10, "day10", true)]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn problem_files_rejected() {
+        let cases = [
+            (
+                "duplicate",
+                [("day01.rs", SOLVED), ("day1.rs", SOLVED)],
+                "both for day 1",
+            ),
+            (
+                "name",
+                [("day01.rs", SOLVED), ("helpers.rs", SOLVED)],
+                "must be named dayNN.rs",
+            ),
+            (
+                "solutions",
+                [("day01.rs", SOLVED), ("day02.rs", "")],
+                "does not define SOLUTIONS",
+            ),
+        ];
+        for (name, files, message) in cases {
+            let dir = temp_dir(name, &files);
+            let err = read_problem_files(&dir, Span::call_site())
+                .err()
+                .unwrap()
+                .to_string();
+            assert!(err.contains(message), "{}", err);
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+        let missing = std::env::temp_dir().join("aoc-macros-missing-dir");
+        assert!(read_problem_files(&missing, Span::call_site()).is_err());
+    }
+
+    #[test]
+    fn example_answers() {
+        assert!(has_answers("part1 = 4\n---\ninput"));
+        assert!(has_answers("  part2 = \"x\"\n---\n"));
+        assert!(!has_answers("# part1 = \n# part2 = \n---\npart1 = 3"));
+        assert!(!has_answers("no header"));
+    }
 }
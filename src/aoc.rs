@@ -1,25 +1,268 @@
+pub mod answers;
+mod error;
+#[cfg(test)]
+mod examples;
+pub mod input;
 mod problems;
 
+pub use error::{AocError, AocErrorKind};
+pub use problems::{problem, PROBLEMS};
+
+use std::{any::Any, io::BufRead, marker::PhantomData};
+
 /// Type alias for a pair of problem solutions
 pub type Solutions = (&'static dyn Aoc, &'static dyn Aoc);
 
+/// The parsed form of an input, as produced by `Aoc::try_parse`
+pub type Parsed = Box<dyn Any>;
+
+/// The answer to a problem part
+///
+/// Solvers can return plain numbers and strings, which convert into the matching variant. The
+/// `Display` implementation is the canonical text of the answer, used both for output and for
+/// comparison with known answers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    /// A signed integer
+    Int(i64),
+    /// An unsigned integer
+    UInt(u64),
+    /// An integer too large for 64 bits
+    BigInt(i128),
+    /// A single line of text
+    Text(String),
+    /// Several lines of text, such as letters drawn in ASCII art
+    Multiline(String),
+}
+
+impl Answer {
+    /// Return true if the answer spans several lines
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Answer::Multiline(_))
+    }
+}
+
+impl std::fmt::Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::UInt(n) => write!(f, "{}", n),
+            Answer::BigInt(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Multiline(s) => write!(f, "{}", s.trim_end_matches('\n')),
+        }
+    }
+}
+
+macro_rules! impl_answer_from {
+    ($variant:ident($target:ty): $($source:ty),*) => {
+        $(
+            impl From<$source> for Answer {
+                fn from(value: $source) -> Answer {
+                    Answer::$variant(value as $target)
+                }
+            }
+        )*
+    };
+}
+
+impl_answer_from!(Int(i64): i8, i16, i32, i64, isize);
+impl_answer_from!(UInt(u64): u8, u16, u32, u64, usize);
+impl_answer_from!(BigInt(i128): i128);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Answer {
+        if value.trim_end_matches('\n').contains('\n') {
+            Answer::Multiline(value)
+        } else {
+            Answer::Text(value)
+        }
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Answer {
+        Answer::from(value.to_string())
+    }
+}
+
 /// A solver for an AOC problem
 pub trait Aoc {
-    /// Solve the problem and return a string representation of the answer
-    fn solve(&self, input: &str) -> String;
+    /// Solve the problem and return the answer, or an error if the input cannot be solved
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError>;
+
+    /// Parse the input without solving the problem
+    ///
+    /// Solvers that do not separate parsing from solving return None. The result can be passed to
+    /// `try_solve_parsed` of either part of the same day, so that both share one parse.
+    fn try_parse(&self, _input: &str) -> Option<Result<Parsed, AocError>> {
+        None
+    }
+
+    /// Solve the problem from the result of `try_parse`
+    ///
+    /// Panics if the solver does not separate parsing from solving or if `parsed` came from a
+    /// different solver.
+    fn try_solve_parsed(&self, _parsed: &Parsed) -> Result<Answer, AocError> {
+        panic!("this solver does not accept parsed input")
+    }
+
+    /// Solve the problem from input read as a stream, for inputs too large to hold in memory
+    ///
+    /// Solvers that need the whole input at once read all of it and pass it to `try_solve`.
+    fn try_solve_stream(&self, input: &mut dyn BufRead) -> Result<Answer, AocError> {
+        let mut text = String::new();
+        input.read_to_string(&mut text)?;
+        self.try_solve(&text)
+    }
+
+    /// Solve the problem and return the answer, or panic if the input cannot be solved
+    #[allow(unused)]
+    fn solve(&self, input: &str) -> Answer {
+        self.try_solve(input)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 }
 
-pub fn solve_problem(day: u8, stage: u8, input: &str) -> String {
-    let problems = problems::all_problems();
-    let &(part1, part2) = problems.get(day as usize - 1).expect("Invalid day");
-    match stage {
-        1 => part1.solve(input),
-        2 => part2.solve(input),
-        _ => panic!("Invalid problem stage"),
+/// A solver that parses its input once and solves both parts from the result
+///
+/// Its `SOLUTIONS` constant adapts it to the pair of `Aoc` solvers a day provides.
+pub trait Solver: Sized + 'static {
+    /// The parsed form of the input
+    type Parsed: 'static;
+
+    /// The pair of solutions for the day, built from this solver
+    const SOLUTIONS: Solutions = (
+        &SolverPart::<Self, 1>(PhantomData),
+        &SolverPart::<Self, 2>(PhantomData),
+    );
+
+    /// Parse the input
+    fn parse(input: &str) -> Result<Self::Parsed, AocError>;
+
+    /// Solve part 1 from the parsed input
+    fn part1(parsed: &Self::Parsed) -> Result<Answer, AocError>;
+
+    /// Solve part 2 from the parsed input
+    fn part2(_parsed: &Self::Parsed) -> Result<Answer, AocError> {
+        Err(AocError::new(AocErrorKind::Unsolved))
     }
 }
 
+/// Adapter that exposes one part of a `Solver` as an `Aoc`
+pub struct SolverPart<S, const PART: u8>(PhantomData<S>);
+
+impl<S: Solver, const PART: u8> SolverPart<S, PART> {
+    fn solve(parsed: &S::Parsed) -> Result<Answer, AocError> {
+        match PART {
+            1 => S::part1(parsed),
+            _ => S::part2(parsed),
+        }
+    }
+}
+
+impl<S: Solver, const PART: u8> Aoc for SolverPart<S, PART> {
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        Self::solve(&S::parse(input)?)
+    }
+
+    fn try_parse(&self, input: &str) -> Option<Result<Parsed, AocError>> {
+        Some(S::parse(input).map(|parsed| Box::new(parsed) as Parsed))
+    }
+
+    fn try_solve_parsed(&self, parsed: &Parsed) -> Result<Answer, AocError> {
+        let parsed = parsed
+            .downcast_ref::<S::Parsed>()
+            .expect("parsed input came from a different solver");
+        Self::solve(parsed)
+    }
+}
+
+/// Placeholder for a part that has not been solved yet
+///
+/// Using it as the second element of a day's `SOLUTIONS` marks the day as having no part 2.
+#[allow(unused)]
+pub struct Unsolved;
+
+impl Aoc for Unsolved {
+    fn try_solve(&self, _input: &str) -> Result<Answer, AocError> {
+        Err(AocError::new(AocErrorKind::Unsolved))
+    }
+}
+
+/// Metadata about a solved day, generated by `define_problems!`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProblemInfo {
+    /// The day number, from 1 to 25
+    pub day: u8,
+    /// The name of the module that holds the day's solutions
+    pub module: &'static str,
+    /// Whether part 2 has been solved
+    pub has_part2: bool,
+}
+
+impl ProblemInfo {
+    /// Return true if the given part has been solved
+    pub fn has_part(&self, part: Part) -> bool {
+        part == Part::One || self.has_part2
+    }
+}
+
+/// One of the two parts of a day's problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    /// Both parts, in order
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    /// Return the part for a 1-based part number, or None if there is no such part
+    pub fn from_number(number: u8) -> Option<Part> {
+        match number {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None,
+        }
+    }
+
+    /// Return the 1-based part number
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+
+    /// Return the solver for this part from a pair of solutions
+    pub fn select(self, solutions: Solutions) -> &'static dyn Aoc {
+        match self {
+            Part::One => solutions.0,
+            Part::Two => solutions.1,
+        }
+    }
+}
+
+impl std::fmt::Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+/// Return the metadata for a day, or None if the day has not been solved
+pub fn info(day: u8) -> Option<&'static ProblemInfo> {
+    PROBLEMS.iter().find(|info| info.day == day)
+}
+
+/// Return every day that has solutions, in ascending order
+pub fn days() -> impl Iterator<Item = u8> {
+    PROBLEMS.iter().map(|info| info.day)
+}
+
 /// Common imports
 pub mod prelude {
-    pub use crate::aoc::{Aoc, Solutions};
+    #[allow(unused_imports)]
+    pub use crate::aoc::{Answer, Aoc, AocError, Solutions, Solver, Unsolved};
 }
@@ -0,0 +1,279 @@
+//! Known-correct answers stored on disk
+
+use std::path::{Path, PathBuf};
+
+use super::Part;
+use crate::util::scanner::{ScanError, Scanner};
+
+/// Name of the directory in the source tree that holds the answer files
+const ANSWER_DIR_NAME: &str = "answers";
+
+/// An error that occurred while reading or writing an answer file
+#[derive(Debug)]
+pub enum AnswerError {
+    /// An answer file is not in the expected format
+    Parse { path: PathBuf, error: ScanError },
+    /// An answer file could not be read or written
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+}
+
+impl std::fmt::Display for AnswerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnswerError::Parse { path, error } => write!(f, "{}: {}", path.display(), error),
+            AnswerError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for AnswerError {}
+
+/// The known answers for the parts of a single day
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DayAnswers {
+    parts: [Option<String>; 2],
+}
+
+impl DayAnswers {
+    /// Return the known answer for a part, if there is one
+    pub fn get(&self, part: Part) -> Option<&str> {
+        self.parts[part.number() as usize - 1].as_deref()
+    }
+
+    /// Set the known answer for a part
+    pub fn set(&mut self, part: Part, answer: impl Into<String>) {
+        self.parts[part.number() as usize - 1] = Some(answer.into());
+    }
+
+    /// Parse the contents of an answer file
+    ///
+    /// The format is the subset of TOML needed to hold two strings: `part1` and `part2` keys set
+    /// to basic strings, multi-line strings or bare integers, with `#` comments. The newlines just
+    /// inside the delimiters of a multi-line string are not part of its value.
+    pub fn parse(input: &str) -> Result<DayAnswers, ScanError> {
+        let mut answers = DayAnswers::default();
+        let mut scanner = Scanner::new(input, false);
+
+        loop {
+            scanner.skip_whitespace();
+            if scanner.try_scan_regex("#[^\n]*").is_some() {
+                continue;
+            }
+            if scanner.is_finished() {
+                break;
+            }
+
+            let key = scanner.scan_regex("part[12]")?;
+            let part = match key.as_str() {
+                "part1" => Part::One,
+                _ => Part::Two,
+            };
+            scanner.try_scan_regex("[ \t]*");
+            scanner.scan_string("=")?;
+            scanner.try_scan_regex("[ \t]*");
+
+            let value = if let Some([value]) =
+                scanner.try_scan_regex_captures(r#"(?s)"""\n?(.*?)\n?""""#)
+            {
+                unescape(value.as_str())
+            } else if let Some([value]) =
+                scanner.try_scan_regex_captures(r#""((?:[^"\\\n]|\\.)*)""#)
+            {
+                unescape(value.as_str())
+            } else {
+                scanner.scan_signed_int()?.as_str().to_string()
+            };
+            answers.set(part, value);
+
+            scanner.try_scan_regex("[ \t]*(#[^\n]*)?");
+            if !scanner.is_finished() {
+                scanner.scan_linebreak()?;
+            }
+        }
+
+        Ok(answers)
+    }
+}
+
+impl std::fmt::Display for DayAnswers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for part in Part::ALL {
+            match self.get(part) {
+                Some(answer) if answer.contains('\n') => writeln!(
+                    f,
+                    "part{} = \"\"\"\n{}\n\"\"\"",
+                    part,
+                    escape(answer, false)
+                )?,
+                Some(answer) => writeln!(f, "part{} = \"{}\"", part, escape(answer, true))?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Escape a string for use inside a basic or multi-line string
+fn escape(value: &str, single_line: bool) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' if single_line => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Resolve the escape sequences in the body of a string
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(c) => result.push(c),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// The collection of answer files, one `dayNN.toml` per day
+///
+/// The answers live in the source tree rather than next to the inputs so that they can be
+/// committed along with the solutions they check.
+#[derive(Debug, Clone)]
+pub struct AnswerStore {
+    dir: PathBuf,
+}
+
+impl Default for AnswerStore {
+    fn default() -> AnswerStore {
+        AnswerStore::new(Path::new(env!("CARGO_MANIFEST_DIR")).join(ANSWER_DIR_NAME))
+    }
+}
+
+impl AnswerStore {
+    /// Create a store that keeps its files in the given directory
+    pub fn new(dir: impl Into<PathBuf>) -> AnswerStore {
+        AnswerStore { dir: dir.into() }
+    }
+
+    /// Return the path of the answer file for a day
+    pub fn path(&self, day: u8) -> PathBuf {
+        self.dir.join(format!("day{:02}.toml", day))
+    }
+
+    /// Load the answers for a day, which are empty if the day has no answer file
+    pub fn load(&self, day: u8) -> Result<DayAnswers, AnswerError> {
+        let path = self.path(day);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(DayAnswers::default())
+            }
+            Err(error) => return Err(AnswerError::Io { path, error }),
+        };
+        DayAnswers::parse(&contents).map_err(|error| AnswerError::Parse { path, error })
+    }
+
+    /// Write the answers for a day, replacing its answer file
+    pub fn save(&self, day: u8, answers: &DayAnswers) -> Result<(), AnswerError> {
+        let path = self.path(day);
+        std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(&path, answers.to_string()))
+            .map_err(|error| AnswerError::Io { path, error })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(part1: Option<&str>, part2: Option<&str>) -> DayAnswers {
+        let mut answers = DayAnswers::default();
+        for (part, answer) in Part::ALL.into_iter().zip([part1, part2]) {
+            if let Some(answer) = answer {
+                answers.set(part, answer);
+            }
+        }
+        answers
+    }
+
+    #[test]
+    fn parse_formats() {
+        let input = "# Day 1\npart1 = 142 # sample\n\npart2=\"a \\\"b\\\" \\\\ c\\td\"\n";
+        assert_eq!(
+            DayAnswers::parse(input).unwrap(),
+            answers(Some("142"), Some("a \"b\" \\ c\td"))
+        );
+
+        let input = "part2 = \"\"\"\n#..\n.#.\n\"\"\"\npart1 = -5\n";
+        assert_eq!(
+            DayAnswers::parse(input).unwrap(),
+            answers(Some("-5"), Some("#..\n.#."))
+        );
+        assert_eq!(DayAnswers::parse("").unwrap(), DayAnswers::default());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for (part1, part2) in [
+            (Some("4361"), Some("467835")),
+            (Some("say \"hi\""), None),
+            (None, Some("C:\\dir\\")),
+            (Some("one\ntwo"), Some("\n\"\"\"\n")),
+            (Some("tab\there"), Some("line\n")),
+            (None, None),
+        ] {
+            let answers = answers(part1, part2);
+            let text = answers.to_string();
+            assert_eq!(DayAnswers::parse(&text).unwrap(), answers, "{}", text);
+        }
+        assert_eq!(
+            answers(Some("a\"b"), Some("x\ny")).to_string(),
+            "part1 = \"a\\\"b\"\npart2 = \"\"\"\nx\ny\n\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn escape_and_unescape() {
+        assert_eq!(escape("a\"b\\c\nd\te", true), "a\\\"b\\\\c\\nd\\te");
+        assert_eq!(escape("a\nb", false), "a\nb");
+        assert_eq!(unescape("a\\\"b\\\\c\\nd\\te"), "a\"b\\c\nd\te");
+        // Unknown escapes keep the escaped character, and a trailing backslash is kept as it is
+        assert_eq!(unescape("\\x\\"), "x\\");
+    }
+
+    #[test]
+    fn malformed_lines() {
+        for (input, line, column) in [
+            ("part3 = 1\n", 1, 1),
+            ("part1 1\n", 1, 7),
+            ("part1 = \"open\n", 1, 9),
+            ("part1 = 1\npart2 = 2 3\n", 2, 11),
+            ("part1 = \"\"\"\nnever closed\n", 1, 11),
+            ("part1 = word\n", 1, 9),
+        ] {
+            let error = DayAnswers::parse(input).unwrap_err();
+            assert_eq!(
+                (error.line(), error.column()),
+                (line, column),
+                "{:?}",
+                input
+            );
+        }
+    }
+}
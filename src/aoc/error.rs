@@ -0,0 +1,92 @@
+use super::Part;
+use crate::util::scanner::ScanError;
+
+/// The ways in which solving a problem can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AocErrorKind {
+    /// The input did not match the format the solver expects
+    Parse(ScanError),
+    /// The input was well-formed but violates an assumption of the solver
+    InvalidInput(String),
+    /// The input is valid but has no answer
+    NoSolution,
+    /// The day or part has not been solved yet
+    Unsolved,
+    /// The solver panicked with the given message
+    Panic(String),
+    /// The input could not be read while the solver was reading it as a stream
+    Read(String),
+}
+
+/// An error from solving a problem, along with the day and part it occurred in if they are known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AocError {
+    day: Option<u8>,
+    part: Option<Part>,
+    kind: AocErrorKind,
+}
+
+impl AocError {
+    /// Create an error of the given kind with no day or part attached
+    pub fn new(kind: AocErrorKind) -> AocError {
+        AocError {
+            day: None,
+            part: None,
+            kind,
+        }
+    }
+
+    /// Create an error for an input that violates an assumption of the solver
+    pub fn invalid_input(message: impl Into<String>) -> AocError {
+        AocError::new(AocErrorKind::InvalidInput(message.into()))
+    }
+
+    /// Create an error for an input that has no answer
+    pub fn no_solution() -> AocError {
+        AocError::new(AocErrorKind::NoSolution)
+    }
+
+    /// Return the kind of error
+    pub fn kind(&self) -> &AocErrorKind {
+        &self.kind
+    }
+
+    /// Attach the day and part to the error, unless it already has them
+    pub fn with_context(mut self, day: u8, part: Part) -> AocError {
+        self.day.get_or_insert(day);
+        self.part.get_or_insert(part);
+        self
+    }
+}
+
+impl From<ScanError> for AocError {
+    fn from(err: ScanError) -> AocError {
+        AocError::new(AocErrorKind::Parse(err))
+    }
+}
+
+impl From<std::io::Error> for AocError {
+    fn from(err: std::io::Error) -> AocError {
+        AocError::new(AocErrorKind::Read(err.to_string()))
+    }
+}
+
+impl std::fmt::Display for AocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.day, self.part) {
+            (Some(day), Some(part)) => write!(f, "day {} part {}: ", day, part)?,
+            (Some(day), None) => write!(f, "day {}: ", day)?,
+            _ => {}
+        }
+        match &self.kind {
+            AocErrorKind::Parse(err) => write!(f, "could not parse input: {}", err),
+            AocErrorKind::InvalidInput(message) => write!(f, "invalid input: {}", message),
+            AocErrorKind::NoSolution => write!(f, "the input has no solution"),
+            AocErrorKind::Unsolved => write!(f, "not solved yet"),
+            AocErrorKind::Panic(message) => write!(f, "solver panicked: {}", message),
+            AocErrorKind::Read(message) => write!(f, "could not read input: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for AocError {}
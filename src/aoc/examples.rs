@@ -0,0 +1,54 @@
+//! Example inputs with known answers, checked by the tests that `define_problems!` generates
+//!
+//! An example file starts with a header in the format of an answer file, giving the expected
+//! answer for one or both parts, followed by a `---` line and then the input itself.
+
+use super::{answers::DayAnswers, problem, Part};
+use crate::util::scanner::{ScanError, Scanner};
+
+/// An example input along with its expected answers
+pub struct Example<'s> {
+    pub answers: DayAnswers,
+    pub input: &'s str,
+}
+
+impl<'s> Example<'s> {
+    /// Split an example file into its header and input and parse the header
+    pub fn parse(text: &'s str) -> Result<Example<'s>, ScanError> {
+        let mut scanner = Scanner::new(text, false);
+        let [header] = scanner
+            .try_scan_regex_captures(r"(?s)(.*?)(?:^|\n)---[ \t]*(?:\r\n|\r|\n|$)")
+            .ok_or_else(|| ScanError::new("a '---' line after the header", text, text.len()))?;
+
+        Ok(Example {
+            answers: DayAnswers::parse(header.as_str())?,
+            input: scanner.remaining(),
+        })
+    }
+}
+
+/// Check a day's solutions against an example, panicking if any answer is wrong
+pub fn check(day: u8, name: &str, text: &str) {
+    let example = Example::parse(text).unwrap_or_else(|err| panic!("{}: {}", name, err));
+    let solutions = problem(day).unwrap_or_else(|| panic!("{}: day {} is not solved", name, day));
+
+    let mut checked = 0;
+    for part in Part::ALL {
+        let Some(expected) = example.answers.get(part) else {
+            continue;
+        };
+        let answer = part
+            .select(solutions)
+            .try_solve(example.input)
+            .unwrap_or_else(|err| panic!("{}: {}", name, err.with_context(day, part)));
+        assert_eq!(
+            answer.to_string(),
+            expected,
+            "{}: wrong answer for part {}",
+            name,
+            part
+        );
+        checked += 1;
+    }
+    assert!(checked > 0, "{}: the header gives no answers", name);
+}
@@ -0,0 +1,187 @@
+//! Locating puzzle inputs on disk
+
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming a directory that is searched for inputs before any other
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Name of the directory that holds the inputs, both in the source tree and next to the binary
+const INPUT_DIR_NAME: &str = "inputs";
+
+/// An error that occurred while locating or reading a puzzle input
+#[derive(Debug)]
+pub enum InputError {
+    /// None of the candidate paths for a day exist
+    NotFound { day: u8, tried: Vec<PathBuf> },
+    /// A candidate path exists but could not be read
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::NotFound { day, tried } => {
+                write!(f, "no input found for day {}, tried:", day)?;
+                for path in tried.iter() {
+                    write!(f, "\n    {}", path.display())?;
+                }
+                Ok(())
+            }
+            InputError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Finds the input file for a day by searching a list of directories
+///
+/// Within each directory, `dayNN.txt` and `dayN.txt` are both accepted. A `dayNN.override.txt` (or
+/// `dayN.override.txt`) in any directory takes precedence over every regular input file, which
+/// makes it easy to try a solver against a different input without touching the real one.
+#[derive(Debug, Clone)]
+pub struct InputLocator {
+    dirs: Vec<PathBuf>,
+}
+
+impl InputLocator {
+    /// Create a locator for the default search path
+    ///
+    /// The directories are, in order: the directory named by `AOC_INPUT_DIR` if it is set,
+    /// `inputs` relative to the working directory, the copy of `inputs` that the build script
+    /// places next to the binary, and `inputs` in the source tree the binary was built from.
+    pub fn from_env() -> InputLocator {
+        let mut dirs = Vec::new();
+
+        if let Some(dir) = std::env::var_os(INPUT_DIR_VAR) {
+            dirs.push(PathBuf::from(dir));
+        }
+        dirs.push(PathBuf::from(INPUT_DIR_NAME));
+        if let Some(exe_dir) = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            dirs.push(exe_dir.join(INPUT_DIR_NAME));
+        }
+        dirs.push(Path::new(env!("CARGO_MANIFEST_DIR")).join(INPUT_DIR_NAME));
+
+        dirs.dedup();
+        InputLocator { dirs }
+    }
+
+    /// Return every path that is tried for a day, in order of preference
+    pub fn candidates(&self, day: u8) -> Vec<PathBuf> {
+        let names = |suffix: &str| {
+            let mut names = vec![format!("day{:02}{}", day, suffix)];
+            if day < 10 {
+                names.push(format!("day{}{}", day, suffix));
+            }
+            names
+        };
+
+        let mut candidates = Vec::new();
+        for suffix in [".override.txt", ".txt"] {
+            for dir in self.dirs.iter() {
+                candidates.extend(names(suffix).iter().map(|name| dir.join(name)));
+            }
+        }
+        candidates
+    }
+
+    /// Return the path of the input for a day
+    pub fn locate(&self, day: u8) -> Result<PathBuf, InputError> {
+        let tried = self.candidates(day);
+        match tried.iter().find(|path| path.is_file()) {
+            Some(path) => Ok(path.clone()),
+            None => Err(InputError::NotFound { day, tried }),
+        }
+    }
+
+    /// Locate the input for a day and read it
+    pub fn read(&self, day: u8) -> Result<String, InputError> {
+        let path = self.locate(day)?;
+        std::fs::read_to_string(&path).map_err(|error| InputError::Io { path, error })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty directory for a test, holding the given files
+    fn temp_dir(name: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc-input-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in files {
+            std::fs::write(dir.join(name), name).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn candidates_in_order() {
+        let locator = InputLocator {
+            dirs: vec![PathBuf::from("a"), PathBuf::from("b")],
+        };
+        let names = |day| {
+            locator
+                .candidates(day)
+                .iter()
+                .map(|path| path.to_str().unwrap().replace('\\', "/"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(7),
+            [
+                "a/day07.override.txt",
+                "a/day7.override.txt",
+                "b/day07.override.txt",
+                "b/day7.override.txt",
+                "a/day07.txt",
+                "a/day7.txt",
+                "b/day07.txt",
+                "b/day7.txt",
+            ]
+        );
+        assert_eq!(
+            names(12),
+            [
+                "a/day12.override.txt",
+                "b/day12.override.txt",
+                "a/day12.txt",
+                "b/day12.txt",
+            ]
+        );
+    }
+
+    #[test]
+    fn locate_prefers_earlier_candidates() {
+        let first = temp_dir("first", &["day3.txt", "day04.txt"]);
+        let second = temp_dir("second", &["day03.txt", "day04.override.txt", "day5.txt"]);
+        let locator = InputLocator {
+            dirs: vec![first.clone(), second.clone()],
+        };
+        assert_eq!(locator.locate(3).unwrap(), first.join("day3.txt"));
+        assert_eq!(
+            locator.locate(4).unwrap(),
+            second.join("day04.override.txt")
+        );
+        assert_eq!(locator.read(5).unwrap(), "day5.txt");
+
+        std::fs::write(first.join("day03.txt"), "padded").unwrap();
+        assert_eq!(locator.read(3).unwrap(), "padded");
+
+        match locator.locate(6) {
+            Err(InputError::NotFound { day: 6, tried }) => {
+                assert_eq!(tried, locator.candidates(6))
+            }
+            other => panic!("expected no input, got {:?}", other),
+        }
+        std::fs::remove_dir_all(first).unwrap();
+        std::fs::remove_dir_all(second).unwrap();
+    }
+}
@@ -1,3 +1,3 @@
 use aoc2023_macros::define_problems;
 
-define_problems!("src/aoc/problems");
+define_problems!("src/aoc/problems", "examples");
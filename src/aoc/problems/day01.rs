@@ -1,4 +1,4 @@
-use crate::aoc::prelude::*;
+use crate::{aoc::prelude::*, util::scanner::Scanner};
 
 pub const SOLUTIONS: Solutions = (&SolutionPart1, &SolutionPart2);
 
@@ -6,82 +6,62 @@ pub struct SolutionPart1;
 pub struct SolutionPart2;
 
 impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
         let mut sum = 0;
         for line in input.lines() {
-            let first_digit_index = line.find(|p: char| p.is_digit(10)).unwrap();
-            let second_digit_index = line.rfind(|p: char| p.is_digit(10)).unwrap();
-            let first_digit = line.chars().nth(first_digit_index).unwrap();
-            let second_digit = line.chars().nth(second_digit_index).unwrap();
-            let number =
-                10 * first_digit.to_digit(10).unwrap() + second_digit.to_digit(10).unwrap();
+            let mut digits = line.chars().filter_map(|c| c.to_digit(10));
+            let first_digit = digits
+                .next()
+                .ok_or_else(|| AocError::invalid_input(format!("no digit in '{}'", line)))?;
+            let second_digit = digits.next_back().unwrap_or(first_digit);
+            let number = 10 * first_digit + second_digit;
 
             sum += number;
         }
-        sum.to_string()
+        Ok(sum.into())
     }
 }
 
-struct DigitScanner<'s> {
-    string: &'s str,
-}
-
-impl DigitScanner<'_> {
-    fn new(string: &str) -> DigitScanner {
-        DigitScanner { string }
-    }
-
-    fn scan(&mut self) -> Option<u8> {
-        if self.string.is_empty() {
-            return None;
-        }
+/// The spelled-out digits, in order of value from one
+const DIGIT_WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
 
-        let string = self.string;
-        self.string = &self.string[1..];
+/// A digit or a spelled-out digit
+const DIGIT: &str = r"\d|one|two|three|four|five|six|seven|eight|nine";
 
-        if let Some(digit) = string.chars().next().unwrap().to_digit(10) {
-            return Some(digit as u8);
+/// Return every digit in a line, spelled out or not, in order
+///
+/// Spelled-out digits can overlap, as in `eightwo`, so each match is only peeked and the scanner
+/// moves on by a single character at a time.
+fn scan_digits(line: &str) -> Vec<u32> {
+    let mut scanner = Scanner::new(line, false);
+    let mut digits = Vec::new();
+    loop {
+        if let Some(token) = scanner.peek_regex(DIGIT) {
+            let digit = token.as_str();
+            digits.push(match DIGIT_WORDS.iter().position(|&word| word == digit) {
+                Some(index) => index as u32 + 1,
+                None => digit.parse().unwrap(),
+            });
         }
-
-        for (idx, word) in [
-            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-        ]
-        .iter()
-        .enumerate()
-        {
-            if string.starts_with(word) {
-                return Some(1 + idx as u8);
-            }
-        }
-
-        None
-    }
-
-    fn scan_next_digit(&mut self) -> Option<u8> {
-        loop {
-            if let Some(digit) = self.scan() {
-                return Some(digit);
-            }
-            if self.string.is_empty() {
-                return None;
-            }
+        if scanner.try_scan_char_if(|_| true).is_none() {
+            return digits;
         }
     }
 }
 
 impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
         let mut sum = 0;
         for line in input.lines() {
-            let mut scanner = DigitScanner::new(line);
-            let first_digit = scanner.scan_next_digit().unwrap();
-            let mut last_digit = first_digit;
-            while let Some(digit) = scanner.scan_next_digit() {
-                last_digit = digit;
-            }
-            let number = 10 * first_digit as u32 + last_digit as u32;
+            let digits = scan_digits(line);
+            let (Some(&first_digit), Some(&last_digit)) = (digits.first(), digits.last()) else {
+                return Err(AocError::invalid_input(format!("no digit in '{}'", line)));
+            };
+            let number = 10 * first_digit + last_digit;
             sum += number;
         }
-        sum.to_string()
+        Ok(sum.into())
     }
 }
@@ -1,4 +1,7 @@
-use crate::{aoc::prelude::*, util::scanner::Scanner};
+use crate::{
+    aoc::prelude::*,
+    util::scanner::{combinators::*, Scan, ScanError, Scanner},
+};
 
 pub struct SolutionPart1;
 pub struct SolutionPart2;
@@ -26,76 +29,66 @@ impl GameHand {
     }
 }
 
-#[derive(Debug, Default, Clone)]
-struct Game {
-    id: i32,
-    hands: Vec<GameHand>,
-}
-
-struct GameIterator<'s> {
-    scanner: Scanner<'s>,
+/// The colour of a cube
+#[derive(Debug, Clone, Copy)]
+enum Color {
+    Red,
+    Green,
+    Blue,
 }
 
-impl GameIterator<'_> {
-    fn new(string: &str) -> GameIterator {
-        GameIterator {
-            scanner: Scanner::new(string, true),
-        }
+impl<'s> Scan<'s> for Color {
+    fn scan(scanner: &mut Scanner<'s>) -> Result<Color, ScanError> {
+        let token = scanner
+            .try_scan_regex("red|green|blue")
+            .ok_or_else(|| scanner.error("a colour: red, green or blue"))?;
+        Ok(match token.as_str() {
+            "red" => Color::Red,
+            "green" => Color::Green,
+            _ => Color::Blue,
+        })
     }
 }
 
-impl Iterator for GameIterator<'_> {
-    type Item = Game;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.scanner.is_finished() {
-            return None;
-        }
-
-        let mut game = Game {
-            id: 0,
-            hands: Vec::new(),
-        };
-
-        self.scanner.scan_string("Game");
-        game.id = self.scanner.scan_signed_int().parse::<i32>();
-        self.scanner.scan_string(":");
-
-        loop {
-            let mut hand = GameHand::default();
-            loop {
-                let count = self.scanner.scan_signed_int().parse::<i32>();
-                let color: &str = self.scanner.scan_regex(r"(red|green|blue)").as_str();
-                let color = match color {
-                    "red" => &mut hand.red,
-                    "green" => &mut hand.green,
-                    "blue" => &mut hand.blue,
-                    _ => panic!("Invalid color"),
-                };
-                *color += count;
-
-                if self.scanner.try_scan_string(",").is_none() {
-                    break;
-                }
-            }
-            game.hands.push(hand);
+/// A number of cubes of one colour, such as `3 blue`
+#[derive(Debug, Clone, Scan)]
+#[scan("{count} {color}")]
+struct Cubes {
+    count: i32,
+    color: Color,
+}
 
-            if self.scanner.try_scan_string(";").is_none() {
-                break;
+impl<'s> Scan<'s> for GameHand {
+    fn scan(scanner: &mut Scanner<'s>) -> Result<GameHand, ScanError> {
+        let mut hand = GameHand::default();
+        for cubes in sep_by1(Cubes::scan, tag(",")).parse(scanner)? {
+            match cubes.color {
+                Color::Red => hand.red += cubes.count,
+                Color::Green => hand.green += cubes.count,
+                Color::Blue => hand.blue += cubes.count,
             }
         }
-
-        Some(game)
+        Ok(hand)
     }
 }
 
+/// One game, such as `Game 1: 3 blue, 4 red; 1 red, 2 green`
+#[derive(Debug, Default, Clone, Scan)]
+#[scan("Game {id}: {hands: sep(';')}")]
+struct Game {
+    id: i32,
+    hands: Vec<GameHand>,
+}
+
 impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
         const MAX_RED: i32 = 12;
         const MAX_GREEN: i32 = 13;
         const MAX_BLUE: i32 = 14;
 
-        let result: i32 = GameIterator::new(input)
+        let result: i32 = Game::scan_all(input)
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
             .filter(|game| {
                 game.hands.iter().all(|hand| {
                     hand.red <= MAX_RED && hand.green <= MAX_GREEN && hand.blue <= MAX_BLUE
@@ -104,13 +97,15 @@ impl Aoc for SolutionPart1 {
             .map(|game| game.id)
             .sum();
 
-        result.to_string()
+        Ok(result.into())
     }
 }
 
 impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        let result: i32 = GameIterator::new(input)
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        let result: i32 = Game::scan_all(input)
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
             .map(|game| {
                 let max_hand = game
                     .hands
@@ -120,6 +115,6 @@ impl Aoc for SolutionPart2 {
             })
             .sum();
 
-        result.to_string()
+        Ok(result.into())
     }
 }
@@ -4,10 +4,9 @@ use nalgebra_glm::IVec2;
 
 use crate::{aoc::prelude::*, util::scanner::*};
 
-pub struct SolutionPart1;
-pub struct SolutionPart2;
+struct Solution;
 
-pub const SOLUTIONS: Solutions = (&SolutionPart1, &SolutionPart2);
+pub const SOLUTIONS: Solutions = Solution::SOLUTIONS;
 
 #[derive(Debug, Default, Clone)]
 struct Schematic {
@@ -88,16 +87,18 @@ impl PartialOrd for SchematicElementHeapKey {
 }
 
 impl Schematic {
-    fn parse(input: &str) -> Schematic {
+    fn parse(input: &str) -> Result<Schematic, ScanError> {
         let mut result = Schematic::default();
         let mut pos = IVec2::new(0, 0);
         let mut scanner = Scanner::new(input, false);
 
         let is_past =
             |pos: IVec2, other: IVec2| pos.y > other.y || (pos.y == other.y && pos.x > other.x);
+        let is_symbol = |c: char| !c.is_ascii_digit() && c != '.';
 
         // Keep track of the schematic elements that might still be adjacent to the current element
-        // by storing them in a binary heap sorted by the position of the element's bottom-right corner
+        // by storing them in a binary heap sorted by the position of the element's bottom-right
+        // corner
         let mut candidates = BinaryHeap::<SchematicElementHeapKey>::new();
 
         loop {
@@ -106,7 +107,7 @@ impl Schematic {
             }
 
             // Remove elements from the heap that are no longer within range
-            while candidates.peek().map_or(false, move |f| is_past(pos, f.0)) {
+            while candidates.peek().is_some_and(move |f| is_past(pos, f.0)) {
                 candidates.pop();
             }
 
@@ -117,7 +118,7 @@ impl Schematic {
                 continue;
             }
 
-            if let Some(dots_token) = scanner.try_scan_regex(r"\.+") {
+            if let Some(dots_token) = scanner.try_scan_while(|c| c == '.') {
                 pos.x += dots_token.len() as i32;
                 continue;
             }
@@ -126,8 +127,8 @@ impl Schematic {
             let element_type = {
                 if let Some(number_token) = scanner.try_scan_unsigned_int() {
                     pos.x += number_token.len() as i32;
-                    Some(SchematicElementType::Number(number_token.parse::<i32>()))
-                } else if let Some(symbol_token) = scanner.try_scan_regex(r"[^0-9.]") {
+                    Some(SchematicElementType::Number(number_token.parse::<i32>()?))
+                } else if let Some(symbol_token) = scanner.try_scan_char_if(is_symbol) {
                     pos.x += symbol_token.len() as i32;
                     Some(SchematicElementType::Symbol)
                 } else {
@@ -159,15 +160,19 @@ impl Schematic {
                 result.elements.push(element);
             }
         }
-        result
+        Ok(result)
     }
 }
 
-impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        let schematic = Schematic::parse(input);
+impl Solver for Solution {
+    type Parsed = Schematic;
 
-        schematic
+    fn parse(input: &str) -> Result<Schematic, AocError> {
+        Ok(Schematic::parse(input)?)
+    }
+
+    fn part1(schematic: &Schematic) -> Result<Answer, AocError> {
+        let sum = schematic
             .elements
             .iter()
             .filter_map(|element| {
@@ -175,28 +180,23 @@ impl Aoc for SolutionPart1 {
                     .then(|| element.number())
                     .flatten()
             })
-            .sum::<i32>()
-            .to_string()
+            .sum::<i32>();
+        Ok(sum.into())
     }
-}
 
-impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        let schematic = Schematic::parse(input);
-
-        schematic
+    fn part2(schematic: &Schematic) -> Result<Answer, AocError> {
+        let sum = schematic
             .elements
             .iter()
-            .filter_map(|element| {
-                (element.adjacent.len() == 2 && !element.is_number()).then(|| {
-                    element
-                        .adjacent
-                        .iter()
-                        .map(|&i| schematic.elements[i].number().unwrap())
-                        .product::<i32>()
-                })
+            .filter(|element| element.adjacent.len() == 2 && !element.is_number())
+            .map(|element| {
+                element
+                    .adjacent
+                    .iter()
+                    .map(|&i| schematic.elements[i].number().unwrap())
+                    .product::<i32>()
             })
-            .sum::<i32>()
-            .to_string()
+            .sum::<i32>();
+        Ok(sum.into())
     }
 }
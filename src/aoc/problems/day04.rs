@@ -1,11 +1,13 @@
-use crate::{aoc::prelude::*, util::scanner::Scanner};
+use crate::{aoc::prelude::*, util::scanner::Scan};
 
 pub struct SolutionPart1;
 pub struct SolutionPart2;
 
 pub const SOLUTIONS: Solutions = (&SolutionPart1, &SolutionPart2);
 
-#[derive(Default, Debug, Clone)]
+/// One card, such as `Card 1: 41 48 83 | 83 86 6 31`
+#[derive(Default, Debug, Clone, Scan)]
+#[scan("Card {id}: {winning_numbers: sep(' ')} | {my_numbers: sep(' ')}")]
 struct Card {
     id: i32,
     winning_numbers: Vec<i32>,
@@ -21,58 +23,12 @@ impl Card {
     }
 }
 
-struct CardIterator<'s> {
-    scanner: Scanner<'s>,
-}
-
-impl CardIterator<'_> {
-    fn new(string: &str) -> CardIterator {
-        CardIterator {
-            scanner: Scanner::new(string, true),
-        }
-    }
-}
-
-impl Iterator for CardIterator<'_> {
-    type Item = Card;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.scanner.is_finished() {
-            return None;
-        }
-
-        let mut card = Card::default();
-
-        self.scanner.scan_string("Card");
-        card.id = self.scanner.scan_signed_int().parse::<i32>();
-        self.scanner.scan_string(":");
-
-        loop {
-            match self.scanner.try_scan_unsigned_int() {
-                Some(token) => card.winning_numbers.push(token.parse::<i32>()),
-                None => break,
-            }
-        }
-
-        self.scanner.scan_string("|");
-
-        loop {
-            match self.scanner.try_scan_unsigned_int() {
-                Some(token) => card.my_numbers.push(token.parse::<i32>()),
-                None => break,
-            }
-        }
-
-        Some(card)
-    }
-}
-
 impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        CardIterator::new(input)
-            .map(|card| i32::pow(2, card.win_count() as u32) as i32 / 2)
-            .sum::<i32>()
-            .to_string()
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        let sum = Card::scan_all(input)
+            .map(|card| card.map(|card| i32::pow(2, card.win_count() as u32) / 2))
+            .sum::<Result<i32, _>>()?;
+        Ok(sum.into())
     }
 }
 
@@ -93,7 +49,9 @@ impl CardCounter {
         let num_copies = self.counts[idx as usize];
         let win_count = card.win_count();
         for i in (idx + 1)..=(idx + win_count) {
-            self.counts.get_mut(i as usize).map(|c| *c += num_copies);
+            if let Some(count) = self.counts.get_mut(i as usize) {
+                *count += num_copies;
+            }
         }
     }
 
@@ -103,14 +61,21 @@ impl CardCounter {
 }
 
 impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        let cards = CardIterator::new(input).collect::<Vec<_>>();
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        let cards = Card::scan_all(input).collect::<Result<Vec<_>, _>>()?;
         let mut counter = CardCounter::new(cards.len());
 
-        for card in cards.iter() {
+        for (i, card) in cards.iter().enumerate() {
+            if card.id as usize != i + 1 {
+                return Err(AocError::invalid_input(format!(
+                    "expected card {} but found card {}",
+                    i + 1,
+                    card.id
+                )));
+            }
             counter.update(card);
         }
 
-        counter.total_count().to_string()
+        Ok(counter.total_count().into())
     }
 }
@@ -1,9 +1,11 @@
-use crate::{aoc::prelude::*, util::scanner::Scanner};
+use crate::{
+    aoc::prelude::*,
+    util::scanner::{pattern, FromCaptures, ScanCaptures, ScanError, Scanner},
+};
 
-pub struct SolutionPart1;
-pub struct SolutionPart2;
+struct Solution;
 
-pub const SOLUTIONS: Solutions = (&SolutionPart1, &SolutionPart2);
+pub const SOLUTIONS: Solutions = Solution::SOLUTIONS;
 
 #[allow(unused)]
 mod key {
@@ -83,7 +85,11 @@ impl Mapping {
         let mut result = Vec::new();
         let mut start = interval.start;
 
-        let Some(mut span_index) = self.spans.iter().position(|span| span.interval.end >= start) else {
+        let first_span = self
+            .spans
+            .iter()
+            .position(|span| span.interval.end >= start);
+        let Some(mut span_index) = first_span else {
             result.push(interval);
             return result;
         };
@@ -113,6 +119,23 @@ impl Mapping {
     }
 }
 
+/// One line of a map, sending `length` values starting at `src` to the same number at `dest`
+struct MappingLine {
+    dest: i64,
+    src: i64,
+    length: usize,
+}
+
+impl FromCaptures<'_> for MappingLine {
+    fn from_captures(captures: &ScanCaptures) -> Result<Self, ScanError> {
+        Ok(MappingLine {
+            dest: captures.parse("dest")?,
+            src: captures.parse("src")?,
+            length: captures.parse("length")?,
+        })
+    }
+}
+
 #[derive(Default, Debug)]
 struct Almanac {
     seeds: Vec<i64>,
@@ -120,30 +143,38 @@ struct Almanac {
 }
 
 impl Almanac {
-    fn parse(input: &str) -> Almanac {
-        let mut scanner = Scanner::new(input, true);
+    fn parse(input: &str) -> Result<Almanac, ScanError> {
+        let mut scanner = Scanner::new(input, false);
         let mut result = Almanac::default();
 
-        scanner.scan_string("seeds:");
-        while let Some(seed_token) = scanner.try_scan_unsigned_int() {
-            result.seeds.push(seed_token.parse::<i64>());
+        let mut seeds = scanner.scan_section()?.scanner(true);
+        seeds.scan_string("seeds:")?;
+        while !seeds.is_finished() {
+            result.seeds.push(seeds.scan_unsigned_int()?.parse::<i64>()?);
         }
 
         for key in 0..key::LOCATION {
-            scanner.scan_regex(".* map:");
-            while let Some([dest_token, src_token, length_token]) =
-                scanner.try_scan_regex_captures(r"(\d+) (\d+) (\d+)")
+            let section = scanner.scan_section()?;
+            if !section
+                .header()
+                .is_some_and(|header| header.as_str().ends_with(" map:"))
             {
-                let dest = dest_token.parse::<i64>();
-                let src = src_token.parse::<i64>();
-                let length = length_token.parse::<usize>();
-                let offset = dest - src;
-
-                result.mappings[key].insert(Interval::start_length(src, length), offset);
+                return Err(section.error("a map header such as 'seed-to-soil map:'"));
+            }
+            let mut lines = section.scanner(true);
+            while !lines.is_finished() {
+                let line = lines.scan_regex_as::<MappingLine>(pattern!(
+                    r"(?P<dest>\d+) (?P<src>\d+) (?P<length>\d+)"
+                ))?;
+                let interval = Interval::start_length(line.src, line.length);
+                result.mappings[key].insert(interval, line.dest - line.src);
             }
         }
 
-        result
+        if let Some(section) = scanner.try_scan_section() {
+            return Err(section.error("end of input"));
+        }
+        Ok(result)
     }
 
     fn map_seed_to_location(&self, seed: i64) -> i64 {
@@ -166,22 +197,27 @@ impl Almanac {
     }
 }
 
-impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        let almanac = Almanac::parse(input);
-        almanac
+impl Solver for Solution {
+    type Parsed = Almanac;
+
+    fn parse(input: &str) -> Result<Almanac, AocError> {
+        Ok(Almanac::parse(input)?)
+    }
+
+    fn part1(almanac: &Almanac) -> Result<Answer, AocError> {
+        let location = almanac
             .seeds
             .iter()
             .map(|&seed| almanac.map_seed_to_location(seed))
             .min()
-            .unwrap()
-            .to_string()
+            .ok_or_else(AocError::no_solution)?;
+        Ok(location.into())
     }
-}
 
-impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        let almanac = Almanac::parse(input);
+    fn part2(almanac: &Almanac) -> Result<Answer, AocError> {
+        if !almanac.seeds.len().is_multiple_of(2) {
+            return Err(AocError::invalid_input("seeds must come in start/length pairs"));
+        }
         let seed_intervals = almanac
             .seeds
             .chunks(2)
@@ -190,18 +226,12 @@ impl Aoc for SolutionPart2 {
                 end: chunk[0] + chunk[1] - 1,
             })
             .collect::<Vec<_>>();
-        seed_intervals
+        let location = seed_intervals
             .iter()
-            .map(|&interval| {
-                almanac
-                    .map_seed_to_location_interval(interval)
-                    .iter()
-                    .map(|i| i.start)
-                    .min()
-                    .unwrap()
-            })
+            .flat_map(|&interval| almanac.map_seed_to_location_interval(interval))
+            .map(|i| i.start)
             .min()
-            .unwrap()
-            .to_string()
+            .ok_or_else(AocError::no_solution)?;
+        Ok(location.into())
     }
 }
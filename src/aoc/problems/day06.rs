@@ -1,4 +1,7 @@
-use crate::{aoc::prelude::*, util::scanner::Scanner};
+use crate::{
+    aoc::prelude::*,
+    util::scanner::{ScanError, Scanner},
+};
 
 pub struct SolutionPart1;
 pub struct SolutionPart2;
@@ -17,35 +20,38 @@ struct RaceIterator<'s> {
 }
 
 impl RaceIterator<'_> {
-    fn new(input: &str) -> RaceIterator {
-        let lines = input.lines().take(2).collect::<Vec<_>>();
-
-        let mut time_scanner = Scanner::new(lines[0], true);
-        let mut distance_scanner = Scanner::new(lines[1], true);
-
-        time_scanner.scan_string("Time:");
-        distance_scanner.scan_string("Distance:");
-
-        RaceIterator {
+    fn new(input: &str) -> Result<RaceIterator<'_>, AocError> {
+        // Read each line with its own scanner, so that the races can be zipped together
+        let mut scanner = Scanner::new(input, true);
+        scanner.scan_string("Time:")?;
+        let time_scanner = scanner.scan_regex("[^\n]*")?.scanner(true);
+        scanner.scan_string("Distance:")?;
+        let distance_scanner = scanner.scan_regex("[^\n]*")?.scanner(true);
+
+        Ok(RaceIterator {
             time_scanner,
             distance_scanner,
-        }
+        })
+    }
+
+    fn scan_race(&mut self) -> Result<Race, ScanError> {
+        let time = self.time_scanner.scan_int::<i64>()?;
+        let distance = self.distance_scanner.scan_int::<i64>()?;
+        Ok(Race {
+            length: time,
+            record: distance,
+        })
     }
 }
 
 impl Iterator for RaceIterator<'_> {
-    type Item = Race;
+    type Item = Result<Race, ScanError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.time_scanner.is_finished() {
             return None;
         }
-        let time = self.time_scanner.scan_unsigned_int().parse::<i64>();
-        let distance = self.distance_scanner.scan_unsigned_int().parse::<i64>();
-        return Some(Race {
-            length: time,
-            record: distance,
-        });
+        Some(self.scan_race())
     }
 }
 
@@ -60,32 +66,47 @@ impl Iterator for RaceIterator<'_> {
 //
 //  (-L ± sqrt(L^2 - 4R)) / 2    <   h    <    (-L ∓ sqrt(L^2 - 4R))
 
-impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        RaceIterator::new(input)
-            .map(|race| {
-                let l = race.length as f64;
+impl Race {
+    /// Return the number of ways to hold the button that beat the record
+    fn ways_to_win(&self) -> i64 {
+        let l = self.length as f64;
 
-                // Add a small epsilon because we need to beat the record, not tie it, and
-                // the solutions of the unperturbed quadratic equation would only tie it.
-                let r = race.record as f64 + 1e-3;
+        // Add a small epsilon because we need to beat the record, not tie it, and
+        // the solutions of the unperturbed quadratic equation would only tie it.
+        let r = self.record as f64 + 1e-3;
 
-                let t1 = -(-l + f64::sqrt(l * l - 4.0 * r)) / 2.0;
-                let t2 = -(-l - f64::sqrt(l * l - 4.0 * r)) / 2.0;
+        let t1 = -(-l + f64::sqrt(l * l - 4.0 * r)) / 2.0;
+        let t2 = -(-l - f64::sqrt(l * l - 4.0 * r)) / 2.0;
 
-                let min_time = f64::ceil(f64::min(t1, t2)) as i64;
-                let max_time = f64::floor(f64::max(t1, t2)) as i64;
+        let min_time = f64::ceil(f64::min(t1, t2)) as i64;
+        let max_time = f64::floor(f64::max(t1, t2)) as i64;
+
+        max_time - min_time + 1
+    }
+}
 
-                max_time - min_time + 1
-            })
-            .product::<i64>()
-            .to_string()
+impl Aoc for SolutionPart1 {
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        let product = RaceIterator::new(input)?
+            .map(|race| Ok(race?.ways_to_win()))
+            .product::<Result<i64, ScanError>>()?;
+        Ok(product.into())
     }
 }
 
 impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        let input = input.replace(" ", "");
-        SolutionPart1.solve(input.as_str())
+    /// The spaces between the numbers on each line are a bad kerning, so each line is read as a
+    /// single race whose digits are split into groups
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        let mut scanner = Scanner::new(input, true);
+        scanner.scan_string("Time:")?;
+        let length = scanner.scan_int_grouped::<i64>(' ')?;
+        scanner.scan_string("Distance:")?;
+        let record = scanner.scan_int_grouped::<i64>(' ')?;
+        if !scanner.is_finished() {
+            return Err(scanner.error("end of input").into());
+        }
+
+        Ok(Race { length, record }.ways_to_win().into())
     }
 }
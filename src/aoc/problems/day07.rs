@@ -1,6 +1,9 @@
-use std::marker::PhantomData;
+use std::{io::BufRead, marker::PhantomData};
 
-use crate::{aoc::prelude::*, util::scanner::Scanner};
+use crate::{
+    aoc::prelude::*,
+    util::scanner::{stream::StreamScanner, Scan, ScanError, Scanner, TokenScanner},
+};
 
 pub struct SolutionPart1;
 pub struct SolutionPart2;
@@ -20,7 +23,7 @@ struct DefaultCardSet;
 impl CardSet for DefaultCardSet {
     fn parse_card(input: char) -> Card {
         Card(match input {
-            x if x >= '2' && x <= '9' => x as u8 - '0' as u8,
+            x @ '2'..='9' => x as u8 - b'0',
             'T' => 10,
             'J' => 11,
             'Q' => 12,
@@ -59,7 +62,7 @@ impl CardSet for JokerCardSet {
     fn parse_card(input: char) -> Card {
         Card(match input {
             'J' => 0,
-            x if x >= '2' && x <= '9' => x as u8 - '0' as u8,
+            x @ '2'..='9' => x as u8 - b'0',
             'T' => 10,
             'Q' => 12,
             'K' => 13,
@@ -134,46 +137,36 @@ enum HandType {
     FiveOfAKind,
 }
 
-struct HandIterator<'s, C: CardSet> {
-    scanner: Scanner<'s>,
-    _marker: PhantomData<C>,
-}
+/// The labels of the five cards in a hand, such as `32T3K`
+#[derive(Debug, Clone)]
+struct Labels(String);
 
-impl<'s, C: CardSet> HandIterator<'s, C> {
-    fn new(input: &'s str) -> Self {
-        Self {
-            scanner: Scanner::new(input, true),
-            _marker: PhantomData,
-        }
+impl<'s> Scan<'s> for Labels {
+    fn scan(scanner: &mut Scanner<'s>) -> Result<Labels, ScanError> {
+        let token = scanner
+            .try_scan_regex("[2-9TJQKA]{5}")
+            .ok_or_else(|| scanner.error("five cards such as '32T3K'"))?;
+        Ok(Labels(token.as_str().to_string()))
     }
 }
 
-impl<'s, C: CardSet> Iterator for HandIterator<'s, C> {
-    type Item = Hand;
+/// One line of input, such as `32T3K 765`, before its cards are read with a card set
+#[derive(Debug, Clone, Scan)]
+#[scan("{cards} {bid}")]
+struct HandLine {
+    cards: Labels,
+    bid: i32,
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.scanner.is_finished() {
-            return None;
-        }
-        let cards = (0..5)
-            .map(|_| {
-                C::parse_card(
-                    self.scanner
-                        .scan_regex("[2-9TJQKA]")
-                        .as_str()
-                        .chars()
-                        .next()
-                        .unwrap(),
-                )
-            })
-            .collect::<Vec<_>>();
-        let bid = self.scanner.scan_unsigned_int().parse::<i32>();
+impl Hand {
+    fn new<C: CardSet>(line: HandLine) -> Hand {
+        let cards = line.cards.0.chars().map(C::parse_card).collect::<Vec<_>>();
         let hand_type = C::get_hand_type(cards.as_slice());
-        Some(Hand {
+        Hand {
             cards,
-            bid,
+            bid: line.bid,
             hand_type,
-        })
+        }
     }
 }
 
@@ -189,28 +182,53 @@ impl<C: CardSet> Default for Solution<C> {
     }
 }
 
+/// Return the total winnings of the hands read from a string or a stream
+fn total_winnings<C: CardSet>(scanner: impl TokenScanner) -> Result<i32, AocError> {
+    let mut hands = scanner
+        .scan_iter::<HandLine>()
+        .map(|line| line.map(Hand::new::<C>))
+        .collect::<Result<Vec<_>, _>>()?;
+    hands.sort();
+
+    Ok(hands
+        .iter()
+        .enumerate()
+        .map(|(i, hand)| ((i + 1) as i32) * hand.bid)
+        .sum::<i32>())
+}
+
 impl<C: CardSet> Aoc for Solution<C> {
-    fn solve(&self, input: &str) -> String {
-        let mut hands = HandIterator::<C>::new(input).collect::<Vec<_>>();
-        hands.sort();
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        Ok(total_winnings::<C>(Scanner::new(input, true))?.into())
+    }
 
-        hands
-            .iter()
-            .enumerate()
-            .map(|(i, hand)| ((i + 1) as i32) * hand.bid)
-            .sum::<i32>()
-            .to_string()
+    /// Read the hands a line at a time, so that inputs too large to hold in memory can be solved
+    fn try_solve_stream(&self, input: &mut dyn BufRead) -> Result<Answer, AocError> {
+        let mut scanner = StreamScanner::new(input, true);
+        let winnings = total_winnings::<C>(&mut scanner)?;
+        match scanner.take_io_error() {
+            Some(err) => Err(err.into()),
+            None => Ok(winnings.into()),
+        }
     }
 }
 
 impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        Solution::<DefaultCardSet>::default().solve(input)
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        Solution::<DefaultCardSet>::default().try_solve(input)
+    }
+
+    fn try_solve_stream(&self, input: &mut dyn BufRead) -> Result<Answer, AocError> {
+        Solution::<DefaultCardSet>::default().try_solve_stream(input)
     }
 }
 
 impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        Solution::<JokerCardSet>::default().solve(input)
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        Solution::<JokerCardSet>::default().try_solve(input)
+    }
+
+    fn try_solve_stream(&self, input: &mut dyn BufRead) -> Result<Answer, AocError> {
+        Solution::<JokerCardSet>::default().try_solve_stream(input)
     }
 }
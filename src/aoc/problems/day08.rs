@@ -1,11 +1,13 @@
 use std::collections::HashMap;
 
-use crate::{aoc::prelude::*, util::scanner::Scanner};
+use crate::{
+    aoc::prelude::*,
+    util::scanner::{pattern, ScanError, Scanner},
+};
 
-pub struct SolutionPart1;
-pub struct SolutionPart2;
+struct Solution;
 
-pub const SOLUTIONS: Solutions = (&SolutionPart1, &SolutionPart2);
+pub const SOLUTIONS: Solutions = Solution::SOLUTIONS;
 
 #[derive(Debug, Clone)]
 struct Node {
@@ -26,67 +28,91 @@ struct Map {
 }
 
 impl Map {
-    pub fn parse(input: &str) -> Map {
-        let mut scanner = Scanner::new(input, true);
-        let path_str = scanner.scan_regex("[LR]+").as_str().to_string();
+    pub fn parse(input: &str) -> Result<Map, ScanError> {
+        let mut sections = Scanner::new(input, false);
+        let mut scanner = sections.scan_section()?.scanner(true);
+        let path_str = scanner.scan_regex("[LR]+")?.as_str().to_string();
+        if !scanner.is_finished() {
+            return Err(scanner.error("a path of 'L' and 'R' steps"));
+        }
 
         let path = path_str
             .chars()
             .map(|c| match c {
                 'L' => Direction::Left,
-                'R' => Direction::Right,
-                _ => panic!("Invalid direction: {}", c),
+                _ => Direction::Right,
             })
             .collect::<Vec<_>>();
 
+        let mut scanner = sections.scan_section()?.scanner(true);
         let mut nodes = HashMap::new();
         while !scanner.is_finished() {
-            let name = scanner.scan_regex("[A-Z0-9]{3}").as_str().to_string();
-            scanner.scan_string("= (");
-            let left = scanner.scan_regex("[A-Z0-9]{3}").as_str().to_string();
-            scanner.scan_string(",");
-            let right = scanner.scan_regex("[A-Z0-9]{3}").as_str().to_string();
-            scanner.scan_string(")");
-            nodes.insert(name, Node { left, right });
+            let captures = scanner
+                .try_scan_regex_named(pattern!(
+                    r"(?x)
+                    (?P<name>[A-Z0-9]{3}) \s* = \s*
+                    \( \s* (?P<left>[A-Z0-9]{3}) \s* , \s* (?P<right>[A-Z0-9]{3}) \s* \)"
+                ))
+                .ok_or_else(|| scanner.error("a node such as 'AAA = (BBB, CCC)'"))?;
+            let name = captures.require("name")?;
+            if nodes.contains_key(name.as_str()) {
+                return Err(name.error(format!("a new node, but '{}' is already defined", name)));
+            }
+            nodes.insert(
+                name.to_string(),
+                Node {
+                    left: captures.require("left")?.to_string(),
+                    right: captures.require("right")?.to_string(),
+                },
+            );
         }
-        Map { path, nodes }
+        if let Some(section) = sections.try_scan_section() {
+            return Err(section.error("end of input"));
+        }
+        Ok(Map { path, nodes })
     }
 
-    pub fn path_length<S: Into<String>, P>(&self, start: S, target: P) -> usize
+    pub fn path_length<S: Into<String>, P>(&self, start: S, target: P) -> Result<usize, AocError>
     where
         P: Fn(&String) -> bool,
     {
         let mut current = start.into();
         for (i, direction) in self.path.iter().cycle().enumerate() {
-            let node = self.nodes.get(&current).unwrap();
+            let node = self
+                .nodes
+                .get(&current)
+                .ok_or_else(|| AocError::invalid_input(format!("unknown node '{}'", current)))?;
             current = match direction {
                 Direction::Left => node.left.clone(),
                 Direction::Right => node.right.clone(),
             };
             if target(&current) {
-                return i + 1;
+                return Ok(i + 1);
             }
         }
         unreachable!();
     }
 }
 
-impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        let map = Map::parse(input);
-        map.path_length("AAA", |x| x == "ZZZ").to_string()
+impl Solver for Solution {
+    type Parsed = Map;
+
+    fn parse(input: &str) -> Result<Map, AocError> {
+        Ok(Map::parse(input)?)
+    }
+
+    fn part1(map: &Map) -> Result<Answer, AocError> {
+        Ok(map.path_length("AAA", |x| x == "ZZZ")?.into())
     }
-}
 
-impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        let map = Map::parse(input);
-        map.nodes
+    fn part2(map: &Map) -> Result<Answer, AocError> {
+        let lengths = map
+            .nodes
             .keys()
             .filter(|x| x.ends_with("A"))
             .map(|x| map.path_length(x, |x| x.ends_with("Z")))
-            .reduce(num::integer::lcm)
-            .unwrap_or(0)
-            .to_string()
+            .collect::<Result<Vec<_>, _>>()?;
+        let steps = lengths.into_iter().reduce(num::integer::lcm).unwrap_or(0);
+        Ok(steps.into())
     }
 }
@@ -1,28 +1,38 @@
-use crate::aoc::prelude::*;
-
-pub struct SolutionPart1;
-pub struct SolutionPart2;
-
-pub const SOLUTIONS: Solutions = (&SolutionPart1, &SolutionPart2);
-
-fn parse_input(input: &str) -> Vec<Vec<i64>> {
-    input
-        .lines()
-        .map(|line| {
-            line.split_whitespace()
-                .map(|num| num.parse::<i64>().unwrap())
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>()
+use crate::{
+    aoc::prelude::*,
+    util::scanner::{ScanError, Scanner},
+};
+
+struct Solution;
+
+pub const SOLUTIONS: Solutions = Solution::SOLUTIONS;
+
+/// Read a sequence of integers from each line
+fn parse_input(input: &str) -> Result<Vec<Vec<i64>>, ScanError> {
+    let mut scanner = Scanner::new(input, false);
+    let mut result = Vec::new();
+    while !scanner.is_finished() {
+        let mut seq = Vec::new();
+        scanner.skip_spaces();
+        while !scanner.at_line_end() {
+            seq.push(scanner.scan_int::<i64>()?);
+            scanner.skip_spaces();
+        }
+        scanner.try_scan_linebreak();
+        result.push(seq);
+    }
+    Ok(result)
 }
 
-fn continue_sequence(seq: &Vec<i64>) -> i64 {
+fn continue_sequence(seq: &[i64]) -> i64 {
     let diffs = seq
-        .into_iter()
-        .zip(seq.into_iter().skip(1))
+        .iter()
+        .zip(seq.iter().skip(1))
         .map(|(a, b)| b - a)
         .collect::<Vec<_>>();
-    let last = seq.last().unwrap();
+    let Some(last) = seq.last() else {
+        return 0;
+    };
     last + if diffs.iter().all(|&diff| diff == 0) {
         0
     } else {
@@ -30,22 +40,26 @@ fn continue_sequence(seq: &Vec<i64>) -> i64 {
     }
 }
 
-impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        parse_input(input)
+impl Solver for Solution {
+    type Parsed = Vec<Vec<i64>>;
+
+    fn parse(input: &str) -> Result<Vec<Vec<i64>>, AocError> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(sequences: &Vec<Vec<i64>>) -> Result<Answer, AocError> {
+        let sum = sequences
             .iter()
             .map(|seq| continue_sequence(seq))
-            .sum::<i64>()
-            .to_string()
+            .sum::<i64>();
+        Ok(sum.into())
     }
-}
 
-impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        parse_input(input)
+    fn part2(sequences: &Vec<Vec<i64>>) -> Result<Answer, AocError> {
+        let sum = sequences
             .iter()
             .map(|seq| continue_sequence(&Iterator::rev(seq.iter().copied()).collect::<Vec<_>>()))
-            .sum::<i64>()
-            .to_string()
+            .sum::<i64>();
+        Ok(sum.into())
     }
 }
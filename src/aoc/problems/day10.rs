@@ -1,13 +1,18 @@
-use std::collections::HashSet;
-
 use nalgebra_glm::IVec2;
 
-use crate::aoc::prelude::*;
+use crate::{
+    aoc::prelude::*,
+    util::{
+        dir::{Cursor, Dir4},
+        grid::Grid,
+        scanner::{ScanError, Scanner},
+        search::{self, GridVisited},
+    },
+};
 
-pub struct SolutionPart1;
-pub struct SolutionPart2;
+struct Solution;
 
-pub const SOLUTIONS: Solutions = (&SolutionPart1, &SolutionPart2);
+pub const SOLUTIONS: Solutions = Solution::SOLUTIONS;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tile {
@@ -21,74 +26,40 @@ enum Tile {
     SouthEast,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Dir {
-    North,
-    South,
-    East,
-    West,
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Winding {
-    CW,
-    CCW,
+    Cw,
+    Ccw,
 }
 
-impl Dir {
-    fn delta(&self) -> IVec2 {
-        use Dir::*;
+impl Winding {
+    /// Return the direction of the inside of a loop with this winding, seen while heading `dir`
+    fn inside(self, dir: Dir4) -> Dir4 {
         match self {
-            North => IVec2::new(0, -1),
-            South => IVec2::new(0, 1),
-            East => IVec2::new(1, 0),
-            West => IVec2::new(-1, 0),
-        }
-    }
-
-    fn opposite(&self) -> Self {
-        use Dir::*;
-        match self {
-            North => South,
-            South => North,
-            East => West,
-            West => East,
-        }
-    }
-
-    fn inside_dir(&self, winding: Winding) -> Self {
-        use Dir::*;
-        let dir = match self {
-            North => East,
-            South => West,
-            East => South,
-            West => North,
-        };
-        match winding {
-            Winding::CW => dir,
-            Winding::CCW => dir.opposite(),
+            Winding::Cw => dir.turn_right(),
+            Winding::Ccw => dir.turn_left(),
         }
     }
 }
 
 impl Tile {
-    fn from_char(c: char) -> Self {
+    fn from_char(c: char) -> Option<Self> {
         use Tile::*;
         match c {
-            '.' => Ground,
-            'S' => Start,
-            '|' => NorthSouth,
-            '-' => EastWest,
-            'L' => NorthEast,
-            'J' => NorthWest,
-            '7' => SouthWest,
-            'F' => SouthEast,
-            _ => panic!("Invalid tile: {}", c),
+            '.' => Some(Ground),
+            'S' => Some(Start),
+            '|' => Some(NorthSouth),
+            '-' => Some(EastWest),
+            'L' => Some(NorthEast),
+            'J' => Some(NorthWest),
+            '7' => Some(SouthWest),
+            'F' => Some(SouthEast),
+            _ => None,
         }
     }
 
-    fn get_dirs(&self) -> (Dir, Dir) {
-        use Dir::*;
+    fn get_dirs(&self) -> (Dir4, Dir4) {
+        use Dir4::*;
         use Tile::*;
 
         match self {
@@ -102,12 +73,12 @@ impl Tile {
         }
     }
 
-    fn connects_to(&self, dir: Dir) -> bool {
+    fn connects_to(&self, dir: Dir4) -> bool {
         self.traverse(dir.opposite()).is_some()
     }
 
-    fn traverse(&self, dir: Dir) -> Option<Dir> {
-        use Dir::*;
+    fn traverse(&self, dir: Dir4) -> Option<Dir4> {
+        use Dir4::*;
         use Tile::*;
 
         match (self, dir) {
@@ -128,134 +99,113 @@ impl Tile {
     }
 }
 
-#[derive(Debug, Clone)]
-struct Grid {
-    tiles: Vec<Tile>,
-    width: i32,
-}
-
-impl Grid {
-    fn new(width: i32, height: i32) -> Self {
-        Self {
-            tiles: vec![Tile::Ground; (width * height) as usize],
-            width,
-        }
-    }
-
-    fn parse(input: &str) -> Self {
-        let mut tiles = Vec::new();
-        let mut width = 0;
-        for line in input.lines() {
-            width = line.len() as i32;
-            for c in line.chars() {
-                tiles.push(Tile::from_char(c));
-            }
-        }
-        Self { tiles, width }
-    }
-
-    fn height(&self) -> i32 {
-        self.tiles.len() as i32 / self.width
-    }
-
-    fn get(&self, coord: IVec2) -> Tile {
-        self.tiles[(coord.y * self.width + coord.x) as usize]
-    }
-
-    fn set(&mut self, coord: IVec2, tile: Tile) {
-        self.tiles[(coord.y * self.width + coord.x) as usize] = tile;
-    }
-
-    fn start(&self) -> IVec2 {
-        let pos = self
-            .tiles
-            .iter()
-            .position(|&t| t == Tile::Start)
-            .expect("No start found");
-        let x = pos as i32 % self.width;
-        let y = pos as i32 / self.width;
-        IVec2::new(x as i32, y as i32)
+fn parse_grid(input: &str) -> Result<Grid<Tile>, ScanError> {
+    let mut scanner = Scanner::new(input, true);
+    let grid = scanner.scan_grid(Tile::from_char)?;
+    if !scanner.is_finished() {
+        return Err(scanner.error("end of input"));
     }
+    Ok(grid)
+}
 
-    fn infer(&self, coord: IVec2) -> Tile {
-        use Dir::*;
-        use Tile::*;
+/// Work out which pipe the start tile stands for from the pipes around it
+///
+/// A neighbour outside the grid connects to nothing.
+fn infer_start(grid: &Grid<Tile>, coord: IVec2) -> Result<Tile, AocError> {
+    use Dir4::*;
+    use Tile::*;
 
-        let north = self.get(coord + North.delta()).connects_to(South);
-        let east = self.get(coord + East.delta()).connects_to(West);
-        let south = self.get(coord + South.delta()).connects_to(North);
-        let west = self.get(coord + West.delta()).connects_to(East);
+    let connects = |dir: Dir4| {
+        grid.get(coord + dir.delta())
+            .is_some_and(|tile| tile.connects_to(dir.opposite()))
+    };
 
-        match (north, east, south, west) {
+    Ok(
+        match (connects(North), connects(East), connects(South), connects(West)) {
             (true, false, true, false) => NorthSouth,
             (false, true, false, true) => EastWest,
             (true, true, false, false) => NorthEast,
             (true, false, false, true) => NorthWest,
             (false, false, true, true) => SouthWest,
             (false, true, true, false) => SouthEast,
-            _ => panic!("{} {} {} {}", north, east, south, west),
-        }
-    }
+            _ => {
+                return Err(AocError::invalid_input(
+                    "start tile does not connect to exactly two pipes",
+                ))
+            }
+        },
+    )
 }
 
-#[derive(Debug, Clone)]
-struct Cursor<'g> {
-    grid: &'g Grid,
-    pos: IVec2,
-    dir: Dir,
+/// Step the cursor into the next pipe and turn it to follow the pipe's bend
+fn follow_pipe(grid: &Grid<Tile>, cursor: &mut Cursor) -> Result<IVec2, AocError> {
+    let pos = cursor.step();
+    cursor.dir = grid
+        .get(pos)
+        .and_then(|tile| tile.traverse(cursor.dir))
+        .ok_or_else(|| {
+            AocError::invalid_input(format!(
+                "pipe loop is broken at {}:{}",
+                pos.y + 1,
+                pos.x + 1
+            ))
+        })?;
+    Ok(pos)
 }
 
-impl<'g> Cursor<'g> {
-    fn new(grid: &'g Grid, pos: IVec2, dir: Dir) -> Self {
-        Self { grid, pos, dir }
-    }
-
-    fn next(&mut self) -> IVec2 {
-        self.pos += self.dir.delta();
-        self.dir = self.grid.get(self.pos).traverse(self.dir).unwrap();
-        self.pos
-    }
+/// Add the ground connected to `coord` to `interior`, if `coord` is itself ground
+fn flood_fill(grid: &Grid<Tile>, coord: IVec2, interior: &mut GridVisited) {
+    let is_ground = |pos: &IVec2| grid.get(*pos) == Some(&Tile::Ground);
+    let ground_neighbours = |&pos: &IVec2| {
+        grid.neighbours4(pos)
+            .filter(|&(_, &tile)| tile == Tile::Ground)
+            .map(|(next, _)| next)
+    };
+    search::dfs_with(Some(coord).filter(is_ground), ground_neighbours, interior).finish();
 }
 
-fn flood_fill(grid: &Grid, coord: IVec2, interior: &mut HashSet<IVec2>) {
-    use Dir::*;
-    use Tile::*;
-
-    let mut queue = vec![coord];
-
-    while !queue.is_empty() {
-        let coord = queue.pop().unwrap();
-        match grid.get(coord) {
-            Ground => {
-                interior.insert(coord);
-                for dir in [North, East, South, West].iter() {
-                    let next = coord + dir.delta();
-                    if !interior.contains(&next) {
-                        queue.push(next);
-                    }
-                }
-            }
-            _ => continue,
-        }
-    }
+/// The grid with its start tile replaced by the pipe it stands for
+struct Maze {
+    grid: Grid<Tile>,
+    start: IVec2,
+    start_tile: Tile,
 }
 
-impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        let mut grid = Grid::parse(input);
+impl Solver for Solution {
+    type Parsed = Maze;
+
+    fn parse(input: &str) -> Result<Maze, AocError> {
+        let mut grid = parse_grid(input)?;
+        let start = grid
+            .find(|&tile| tile == Tile::Start)
+            .ok_or_else(|| AocError::invalid_input("no start tile"))?;
+        let start_tile = infer_start(&grid, start)?;
+        grid[start] = start_tile;
+        Ok(Maze {
+            grid,
+            start,
+            start_tile,
+        })
+    }
 
-        let start_coord = grid.start();
-        let start_tile = grid.infer(start_coord);
+    fn part1(maze: &Maze) -> Result<Answer, AocError> {
+        let Maze {
+            grid,
+            start,
+            start_tile,
+        } = maze;
         let start_dirs = start_tile.get_dirs();
-        grid.set(start_coord, start_tile);
 
-        let mut cursor1 = Cursor::new(&grid, start_coord, start_dirs.0);
-        let mut cursor2 = Cursor::new(&grid, start_coord, start_dirs.1);
+        let mut cursor1 = Cursor::new(*start, start_dirs.0);
+        let mut cursor2 = Cursor::new(*start, start_dirs.1);
 
         let mut steps = 0;
         loop {
             let (_, coord2) = (cursor1.pos, cursor2.pos);
-            let (next1, next2) = (cursor1.next(), cursor2.next());
+            let (next1, next2) = (
+                follow_pipe(grid, &mut cursor1)?,
+                follow_pipe(grid, &mut cursor2)?,
+            );
 
             steps += 1;
 
@@ -264,27 +214,27 @@ impl Aoc for SolutionPart1 {
             }
         }
 
-        steps.to_string()
+        Ok(steps.into())
     }
-}
 
-impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        let mut grid = Grid::parse(input);
-        let mut clean_grid = Grid::new(grid.width, grid.height());
-        let start_coord = grid.start();
-        let start_tile = grid.infer(start_coord);
+    fn part2(maze: &Maze) -> Result<Answer, AocError> {
+        let Maze {
+            grid,
+            start: start_coord,
+            start_tile,
+        } = maze;
+        let start_coord = *start_coord;
+        let mut clean_grid = Grid::new(grid.width(), grid.height(), Tile::Ground);
         let (start_dir, _) = start_tile.get_dirs();
-        grid.set(start_coord, start_tile);
 
         // Clean all the junk out of the grid and detect path winding
-        let mut cursor = Cursor::new(&grid, start_coord, start_dir);
+        let mut cursor = Cursor::new(start_coord, start_dir);
         let mut winding = 0;
         loop {
-            clean_grid.set(cursor.pos, grid.get(cursor.pos));
+            clean_grid[cursor.pos] = grid[cursor.pos];
 
             let p1 = cursor.pos;
-            let next = cursor.next();
+            let next = follow_pipe(grid, &mut cursor)?;
             let p2 = cursor.pos;
 
             winding += (p2.x - p1.x) * (p2.y + p1.y);
@@ -294,21 +244,21 @@ impl Aoc for SolutionPart2 {
             }
         }
         let winding = if winding < 0 {
-            Winding::CW
+            Winding::Cw
         } else {
-            Winding::CCW
+            Winding::Ccw
         };
 
         // Follow path and flood fill all adjacent interiors
-        let mut cursor = Cursor::new(&grid, start_coord, start_dir);
-        let mut interior = HashSet::<IVec2>::new();
+        let mut cursor = Cursor::new(start_coord, start_dir);
+        let mut interior = GridVisited::new(clean_grid.size());
         loop {
             let in_dir = cursor.dir;
-            let next = cursor.next();
+            let next = follow_pipe(grid, &mut cursor)?;
             let out_dir = cursor.dir;
 
             for dir in [in_dir, out_dir] {
-                let flood_start = cursor.pos + dir.inside_dir(winding).delta();
+                let flood_start = cursor.pos + winding.inside(dir).delta();
                 flood_fill(&clean_grid, flood_start, &mut interior);
             }
 
@@ -316,6 +266,6 @@ impl Aoc for SolutionPart2 {
                 break;
             }
         }
-        interior.len().to_string()
+        Ok(interior.len().into())
     }
 }
@@ -1,19 +1,22 @@
-use std::collections::HashMap;
+use nalgebra_glm::{I64Vec2, IVec2};
 
-use nalgebra_glm::I64Vec2;
+use crate::{
+    aoc::prelude::*,
+    util::{
+        grid::Grid,
+        scanner::{ScanError, Scanner},
+    },
+};
 
-use crate::aoc::prelude::*;
+struct Solution;
 
-pub struct SolutionPart1;
-pub struct SolutionPart2;
-
-pub const SOLUTIONS: Solutions = (&SolutionPart1, &SolutionPart2);
+pub const SOLUTIONS: Solutions = Solution::SOLUTIONS;
 
 #[derive(Debug, Clone)]
 struct Universe {
-    galaxies: HashMap<I64Vec2, i64>,
-    width: i64,
-    height: i64,
+    /// True for each cell that holds a galaxy
+    grid: Grid<bool>,
+    galaxies: Vec<IVec2>,
 }
 
 struct ExpandedUniverse<'u> {
@@ -23,24 +26,18 @@ struct ExpandedUniverse<'u> {
 }
 
 impl Universe {
-    fn parse(input: &str) -> Self {
-        let mut galaxies = HashMap::new();
-
-        let width = input.lines().next().unwrap().len() as i64;
-        let height = input.lines().count() as i64;
-
-        for (y, line) in input.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                if c == '#' {
-                    galaxies.insert(I64Vec2::new(x as i64, y as i64), galaxies.len() as i64);
-                }
-            }
-        }
-        Self {
-            galaxies,
-            width,
-            height,
+    fn parse(input: &str) -> Result<Self, ScanError> {
+        let mut scanner = Scanner::new(input, true);
+        let grid = scanner.scan_grid(|c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })?;
+        if !scanner.is_finished() {
+            return Err(scanner.error("end of input"));
         }
+        let galaxies = grid.positions(|&galaxy| galaxy).collect();
+        Ok(Self { grid, galaxies })
     }
 }
 
@@ -50,31 +47,17 @@ impl<'u> ExpandedUniverse<'u> {
         let mut offsets_y = Vec::new();
 
         let mut offset = 0;
-        for x in 0..universe.width {
+        for mut column in universe.grid.columns() {
             offsets_x.push(offset);
-
-            let mut is_empty = true;
-            for y in 0..universe.height {
-                if universe.galaxies.contains_key(&I64Vec2::new(x, y)) {
-                    is_empty = false;
-                }
-            }
-            if is_empty {
+            if column.all(|&galaxy| !galaxy) {
                 offset += expansion_factor - 1;
             }
         }
 
         offset = 0;
-        for y in 0..universe.height {
+        for row in universe.grid.rows() {
             offsets_y.push(offset);
-
-            let mut is_empty = true;
-            for x in 0..universe.width {
-                if universe.galaxies.contains_key(&I64Vec2::new(x, y)) {
-                    is_empty = false;
-                }
-            }
-            if is_empty {
+            if row.iter().all(|&galaxy| !galaxy) {
                 offset += expansion_factor - 1;
             }
         }
@@ -86,61 +69,51 @@ impl<'u> ExpandedUniverse<'u> {
         }
     }
 
-    fn iter_galaxies(&'u self) -> impl Iterator<Item = (I64Vec2, i64)> + 'u {
+    fn iter_galaxies(&'u self) -> impl Iterator<Item = (I64Vec2, usize)> + 'u {
         self.universe
             .galaxies
             .iter()
-            .map(move |(coord, &idx)| (self.map(coord), idx))
+            .enumerate()
+            .map(move |(idx, &coord)| (self.map(coord), idx))
     }
 
-    fn map(&self, coord: &I64Vec2) -> I64Vec2 {
+    fn map(&self, coord: IVec2) -> I64Vec2 {
         I64Vec2::new(
-            coord.x + self.offsets_x[coord.x as usize],
-            coord.y + self.offsets_y[coord.y as usize],
+            coord.x as i64 + self.offsets_x[coord.x as usize],
+            coord.y as i64 + self.offsets_y[coord.y as usize],
         )
     }
 }
 
-struct Solution {
-    expansion_factor: i64,
+/// Sum the distances between every pair of galaxies once empty rows and columns are expanded
+fn sum_distances(universe: &Universe, expansion_factor: i64) -> i64 {
+    let expanded_universe = ExpandedUniverse::new(universe, expansion_factor);
+
+    expanded_universe
+        .iter_galaxies()
+        .flat_map(|(coord_a, idx_a)| {
+            expanded_universe
+                .iter_galaxies()
+                .filter(move |&(_, idx_b)| idx_a < idx_b)
+                .map(move |(coord_b, _)| {
+                    i64::abs(coord_a.x - coord_b.x) + i64::abs(coord_a.y - coord_b.y)
+                })
+        })
+        .fold(0, i64::wrapping_add)
 }
 
-impl Solution {
-    fn new(expansion_factor: i64) -> Self {
-        Self { expansion_factor }
-    }
-}
+impl Solver for Solution {
+    type Parsed = Universe;
 
-impl Aoc for Solution {
-    fn solve(&self, input: &str) -> String {
-        let universe = Universe::parse(input);
-        let expanded_universe = ExpandedUniverse::new(&universe, self.expansion_factor);
-
-        expanded_universe
-            .iter_galaxies()
-            .flat_map(|(coord_a, idx_a)| {
-                expanded_universe
-                    .iter_galaxies()
-                    .filter_map(move |(coord_b, idx_b)| {
-                        (idx_a < idx_b).then(|| {
-                            i64::abs(coord_a.x - coord_b.x) + i64::abs(coord_a.y - coord_b.y)
-                        })
-                    })
-            })
-            .reduce(i64::wrapping_add)
-            .unwrap()
-            .to_string()
+    fn parse(input: &str) -> Result<Universe, AocError> {
+        Ok(Universe::parse(input)?)
     }
-}
 
-impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        Solution::new(2).solve(input)
+    fn part1(universe: &Universe) -> Result<Answer, AocError> {
+        Ok(sum_distances(universe, 2).into())
     }
-}
 
-impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        Solution::new(1000000).solve(input)
+    fn part2(universe: &Universe) -> Result<Answer, AocError> {
+        Ok(sum_distances(universe, 1000000).into())
     }
 }
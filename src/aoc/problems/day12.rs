@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use crate::{aoc::prelude::*, util::scanner::Scanner};
+use crate::{
+    aoc::prelude::*,
+    util::scanner::{combinators::*, ScanError, Scanner, TokenScanner},
+};
 
 pub struct SolutionPart1;
 pub struct SolutionPart2;
@@ -24,41 +27,52 @@ impl Spring {
     }
 }
 
-struct ConditionRecordIterator<'s> {
-    scanner: Scanner<'s>,
+/// Reads condition records a line at a time from a string or a stream
+///
+/// The scanner must not ignore whitespace, so that each record is read from a line of its own.
+struct ConditionRecordIterator<S> {
+    scanner: S,
 }
 
-impl<'s> ConditionRecordIterator<'s> {
-    fn new(input: &'s str) -> Self {
-        Self {
-            scanner: Scanner::new(input, true),
-        }
+impl<S: TokenScanner> ConditionRecordIterator<S> {
+    fn new(scanner: S) -> Self {
+        Self { scanner }
+    }
+
+    fn scan_record(&mut self) -> Result<ConditionRecord, ScanError> {
+        let Some(springs) = self
+            .scanner
+            .try_scan_while(|c| matches!(c, '?' | '.' | '#'))
+            .map(|token| token.as_str().chars().map(Spring::from_char).collect())
+        else {
+            return Err(self.scanner.error("spring conditions"));
+        };
+        let groups = self.scanner.scan_with(|scanner| {
+            scanner.skip_spaces();
+            let groups = sep_by1(unsigned::<i64>(), tag(",")).parse(scanner)?;
+            scanner.skip_spaces();
+            if !scanner.is_finished() {
+                scanner.scan_linebreak()?;
+            }
+            Ok(groups)
+        })?;
+
+        Ok(ConditionRecord {
+            states: springs,
+            groups,
+        })
     }
 }
 
-impl Iterator for ConditionRecordIterator<'_> {
-    type Item = ConditionRecord;
+impl<S: TokenScanner> Iterator for ConditionRecordIterator<S> {
+    type Item = Result<ConditionRecord, ScanError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        self.scanner.scan_with(|scanner| scanner.skip_whitespace());
         if self.scanner.is_finished() {
             return None;
         }
-
-        let mut springs = Vec::new();
-        let mut groups = Vec::new();
-
-        while let Some(token) = self.scanner.try_scan_regex("[?.#]") {
-            springs.push(Spring::from_char(token.parse::<char>()));
-        }
-        while let Some(token) = self.scanner.try_scan_unsigned_int() {
-            groups.push(token.parse::<i64>());
-            self.scanner.try_scan_string(",");
-        }
-
-        Some(ConditionRecord {
-            states: springs,
-            groups,
-        })
+        Some(self.scan_record())
     }
 }
 
@@ -71,8 +85,7 @@ struct ConditionRecord {
 impl ConditionRecord {
     fn unfold(&self, count: usize) -> Self {
         Self {
-            states: std::iter::repeat(self.states.clone())
-                .take(count)
+            states: std::iter::repeat_n(self.states.clone(), count)
                 .collect::<Vec<_>>()
                 .join(&Spring::Unknown),
             groups: self.groups.repeat(count),
@@ -105,13 +118,9 @@ impl ConditionRecord {
                 .unwrap_or((None, &[]));
             let result = (|| match state.head {
                 None => {
-                    if state.group_prefix == 0 && state.groups.is_empty() {
-                        return 1;
-                    } else if state.groups.len() == 1 && state.groups[0] == state.group_prefix {
-                        return 1;
-                    } else {
-                        return 0;
-                    }
+                    let complete = (state.group_prefix == 0 && state.groups.is_empty())
+                        || (state.groups.len() == 1 && state.groups[0] == state.group_prefix);
+                    complete as i64
                 }
                 Some(Spring::Good) => {
                     if state.group_prefix > 0 {
@@ -128,7 +137,7 @@ impl ConditionRecord {
                             cache,
                         );
                     }
-                    return count_arrangements_inner(
+                    count_arrangements_inner(
                         State {
                             group_prefix: 0,
                             head: tail_head,
@@ -136,19 +145,17 @@ impl ConditionRecord {
                             ..state
                         },
                         cache,
-                    );
+                    )
                 }
                 Some(Spring::Bad) => {
                     if state.group_prefix > 0 {
                         if state.group_prefix >= state.groups[0] {
                             return 0;
                         }
-                    } else {
-                        if state.groups.is_empty() {
-                            return 0;
-                        }
+                    } else if state.groups.is_empty() {
+                        return 0;
                     }
-                    return count_arrangements_inner(
+                    count_arrangements_inner(
                         State {
                             group_prefix: state.group_prefix + 1,
                             head: tail_head,
@@ -156,7 +163,7 @@ impl ConditionRecord {
                             ..state
                         },
                         cache,
-                    );
+                    )
                 }
                 Some(Spring::Unknown) => {
                     let bad = count_arrangements_inner(
@@ -173,7 +180,7 @@ impl ConditionRecord {
                         },
                         cache,
                     );
-                    return bad + good;
+                    bad + good
                 }
             })();
             cache.insert(state, result);
@@ -206,22 +213,22 @@ impl Solution {
 }
 
 impl Aoc for Solution {
-    fn solve(&self, input: &str) -> String {
-        ConditionRecordIterator::new(input)
-            .map(|rec| rec.unfold(self.unfold).count_arrangements())
-            .sum::<i64>()
-            .to_string()
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        let sum = ConditionRecordIterator::new(Scanner::new(input, false))
+            .map(|rec| rec.map(|rec| rec.unfold(self.unfold).count_arrangements()))
+            .sum::<Result<i64, _>>()?;
+        Ok(sum.into())
     }
 }
 
 impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        Solution::new(1).solve(input)
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        Solution::new(1).try_solve(input)
     }
 }
 
 impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        Solution::new(5).solve(input)
+    fn try_solve(&self, input: &str) -> Result<Answer, AocError> {
+        Solution::new(5).try_solve(input)
     }
 }
@@ -0,0 +1,626 @@
+//! Command-line interface for running, timing and scaffolding solutions
+
+mod bench;
+mod list;
+mod new;
+mod run;
+mod test;
+mod verify;
+
+use std::{
+    cell::{Cell, OnceCell},
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    process::ExitCode,
+    time::Duration,
+};
+
+use crate::aoc::{
+    self,
+    input::{InputError, InputLocator},
+    Answer, Aoc, AocError, AocErrorKind, Parsed, Part,
+};
+
+pub const USAGE: &str = "\
+Usage:
+    aoc run <days> [<part>] [--input <path> | --stdin] [--trace <sink> | --stream]
+    aoc bench <days> [<part>] [--iterations <n>] [--warmup <n>] [--json]
+              [--input <path> | --stdin]
+    aoc test [<days>] [<part>]
+    aoc verify [<days>] [<part>] [--record]
+    aoc list
+    aoc new <day> [--from <path>]
+    aoc <day> <part>
+
+Days may be a single day (5), a range (1..=12, 1..13, 1-12), a comma-separated
+list of those (1,3,5..=7) or `all`. Parts may be 1, 2 or `both` (the default).
+
+Inputs are read from `dayNN.txt` (or `dayNN.override.txt`, which takes precedence)
+in $AOC_INPUT_DIR, ./inputs, or the inputs directory next to the binary.
+
+Known answers are kept in answers/dayNN.toml in the source tree. `test` fails on
+any answer that differs from them, `verify` shows every answer next to them, and
+`verify --record` saves new or changed answers once confirmed.
+
+Options:
+    -p, --part <part>       Select the part to run
+    -i, --input <path>      Read the input from a file (single day only)
+        --stdin             Read the input from standard input (single day only)
+        --trace <sink>      Trace what the parsers scan to `stderr`, to a file, or
+                            as the input with scanned tokens marked (`annotate`)
+                            (single day only)
+        --stream            Read the input as a stream, a line at a time for the
+                            solvers that support it (one part only with --stdin)
+    -n, --iterations <n>    Number of timed runs per part when benchmarking
+        --warmup <n>        Number of untimed runs per part before benchmarking
+        --json              Print benchmark results as JSON
+        --record            Save new and changed answers when verifying
+        --from <path>       Take the example for a new day from a saved puzzle page
+    -h, --help              Print this message";
+
+/// Exit code for a run in which every selected solution succeeded
+pub const EXIT_SUCCESS: u8 = 0;
+/// Exit code for a run in which at least one solution failed
+pub const EXIT_FAILURE: u8 = 1;
+/// Exit code for invalid command-line arguments
+pub const EXIT_USAGE: u8 = 2;
+/// Exit code for an input that could not be read
+pub const EXIT_INPUT: u8 = 3;
+
+/// An error in the command-line arguments
+#[derive(Debug, Clone)]
+pub struct UsageError(String);
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+macro_rules! usage_error {
+    ($($arg:tt)*) => {
+        UsageError(format!($($arg)*))
+    };
+}
+
+/// A parsed command line
+#[derive(Debug, Clone)]
+pub enum Command {
+    Run(Selection, Option<run::Trace>, bool),
+    Bench(Selection, bench::Options),
+    Test(Selection),
+    Verify(Selection, bool),
+    List,
+    New(u8, Option<PathBuf>),
+    Help,
+}
+
+/// The days and parts a command should operate on, and where to read their input from
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub days: Vec<u8>,
+    pub parts: Vec<Part>,
+    pub input: InputSource,
+}
+
+/// Where to read a day's puzzle input from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    /// The day's file in the inputs directory
+    Default,
+    /// An explicit file
+    File(PathBuf),
+    /// Standard input
+    Stdin,
+}
+
+/// Command-line arguments split into positional arguments and options
+#[derive(Debug, Default)]
+struct Arguments {
+    positional: VecDeque<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Arguments {
+    /// Options that must be followed by a value
+    const VALUE_OPTIONS: [&'static str; 6] =
+        ["part", "input", "iterations", "warmup", "from", "trace"];
+    /// Options that are plain switches
+    const SWITCH_OPTIONS: [&'static str; 5] = ["stdin", "stream", "record", "json", "help"];
+
+    fn parse(args: &[String]) -> Result<Arguments, UsageError> {
+        let mut result = Arguments::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = if let Some(long) = arg.strip_prefix("--") {
+                match long.split_once('=') {
+                    Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                    None => (long.to_string(), None),
+                }
+            } else if arg.len() == 2 && arg.starts_with('-') && !arg.ends_with(char::is_numeric) {
+                let name = match &arg[1..] {
+                    "p" => "part",
+                    "i" => "input",
+                    "n" => "iterations",
+                    "h" => "help",
+                    short => return Err(usage_error!("unknown option '-{}'", short)),
+                };
+                (name.to_string(), None)
+            } else {
+                result.positional.push_back(arg.clone());
+                continue;
+            };
+
+            if Self::VALUE_OPTIONS.contains(&name.as_str()) {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => args
+                        .next()
+                        .cloned()
+                        .ok_or_else(|| usage_error!("option '--{}' requires a value", name))?,
+                };
+                result.options.push((name, Some(value)));
+            } else if Self::SWITCH_OPTIONS.contains(&name.as_str()) {
+                if inline_value.is_some() {
+                    return Err(usage_error!("option '--{}' does not take a value", name));
+                }
+                result.options.push((name, None));
+            } else {
+                return Err(usage_error!("unknown option '--{}'", name));
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn next_positional(&mut self) -> Option<String> {
+        self.positional.pop_front()
+    }
+
+    fn has_switch(&mut self, name: &str) -> bool {
+        let count = self.options.len();
+        self.options.retain(|(option, _)| option != name);
+        self.options.len() != count
+    }
+
+    fn take_value(&mut self, name: &str) -> Result<Option<String>, UsageError> {
+        let mut values = Vec::new();
+        self.options.retain(|(option, value)| {
+            if option == name {
+                values.extend(value.clone());
+                false
+            } else {
+                true
+            }
+        });
+        if values.len() > 1 {
+            return Err(usage_error!("option '--{}' given more than once", name));
+        }
+        Ok(values.pop())
+    }
+
+    /// Fail if any arguments were not consumed by the command
+    fn finish(self) -> Result<(), UsageError> {
+        if let Some(arg) = self.positional.front() {
+            return Err(usage_error!("unexpected argument '{}'", arg));
+        }
+        if let Some((option, _)) = self.options.first() {
+            return Err(usage_error!("option '--{}' is not valid here", option));
+        }
+        Ok(())
+    }
+}
+
+impl Command {
+    /// Parse a command from the program arguments, excluding the program name
+    pub fn parse(args: &[String]) -> Result<Command, UsageError> {
+        let mut args = Arguments::parse(args)?;
+
+        if args.has_switch("help") {
+            return Ok(Command::Help);
+        }
+
+        let command = match args.positional.front().map(String::as_str) {
+            None => Command::Help,
+            Some("help") => {
+                args.next_positional();
+                Command::Help
+            }
+            Some("run") => {
+                args.next_positional();
+                Command::parse_run(&mut args)?
+            }
+            Some("bench") => {
+                args.next_positional();
+                let options = bench::Options {
+                    iterations: match args.take_value("iterations")? {
+                        Some(value) => parse_count(&value)?,
+                        None => bench::DEFAULT_ITERATIONS,
+                    },
+                    warmup: match args.take_value("warmup")? {
+                        Some(value) => value
+                            .parse::<usize>()
+                            .map_err(|_| usage_error!("invalid count '{}'", value))?,
+                        None => bench::DEFAULT_WARMUP,
+                    },
+                    json: args.has_switch("json"),
+                };
+                Command::Bench(Selection::parse(&mut args, None)?, options)
+            }
+            Some("test") => {
+                args.next_positional();
+                Command::Test(Selection::parse(&mut args, Some("all"))?)
+            }
+            Some("verify") => {
+                args.next_positional();
+                let record = args.has_switch("record");
+                let selection = Selection::parse(&mut args, Some("all"))?;
+                if selection.input != InputSource::Default {
+                    return Err(usage_error!(
+                        "answers can only be verified against the default inputs"
+                    ));
+                }
+                Command::Verify(selection, record)
+            }
+            Some("list") => {
+                args.next_positional();
+                Command::List
+            }
+            Some("new") => {
+                args.next_positional();
+                let day = args
+                    .next_positional()
+                    .ok_or_else(|| usage_error!("missing day"))?;
+                Command::New(
+                    parse_day(&day)?,
+                    args.take_value("from")?.map(PathBuf::from),
+                )
+            }
+            // `aoc <day> <part>` is kept as a shorthand for `aoc run <day> <part>`
+            Some(_) => Command::parse_run(&mut args)?,
+        };
+
+        args.finish()?;
+        Ok(command)
+    }
+
+    fn parse_run(args: &mut Arguments) -> Result<Command, UsageError> {
+        let trace = args
+            .take_value("trace")?
+            .map(|value| run::Trace::parse(&value));
+        let stream = args.has_switch("stream");
+        let selection = Selection::parse(args, None)?;
+        if trace.is_some() && selection.days.len() != 1 {
+            return Err(usage_error!("--trace requires exactly one day"));
+        }
+        if trace.is_some() && stream {
+            return Err(usage_error!("--trace and --stream are exclusive"));
+        }
+        // Standard input can only be read once, so it can only be streamed to one part
+        if stream && selection.input == InputSource::Stdin && selection.parts.len() != 1 {
+            return Err(usage_error!(
+                "--stream with --stdin requires exactly one part"
+            ));
+        }
+        Ok(Command::Run(selection, trace, stream))
+    }
+
+    /// Execute the command and return the process exit code
+    pub fn execute(self) -> ExitCode {
+        let code = match self {
+            Command::Run(selection, trace, stream) => {
+                run::execute(&selection, trace.as_ref(), stream)
+            }
+            Command::Bench(selection, options) => bench::execute(&selection, &options),
+            Command::Test(selection) => test::execute(&selection),
+            Command::Verify(selection, record) => verify::execute(&selection, record),
+            Command::List => list::execute(),
+            Command::New(day, from) => new::execute(day, from.as_deref()),
+            Command::Help => {
+                println!("{}", USAGE);
+                EXIT_SUCCESS
+            }
+        };
+        ExitCode::from(code)
+    }
+}
+
+impl Selection {
+    fn parse(args: &mut Arguments, default_days: Option<&str>) -> Result<Selection, UsageError> {
+        let days = args
+            .next_positional()
+            .or_else(|| default_days.map(str::to_string))
+            .ok_or_else(|| usage_error!("missing days"))?;
+        let days = parse_days(&days)?;
+
+        let part = match (args.take_value("part")?, args.next_positional()) {
+            (Some(_), Some(_)) => return Err(usage_error!("part given more than once")),
+            (part, None) | (None, part) => part,
+        };
+        let parts = match part.as_deref() {
+            None | Some("both") | Some("all") => Part::ALL.to_vec(),
+            Some(part) => vec![part
+                .parse::<u8>()
+                .ok()
+                .and_then(Part::from_number)
+                .ok_or_else(|| usage_error!("invalid part '{}'", part))?],
+        };
+
+        let input = match (args.take_value("input")?, args.has_switch("stdin")) {
+            (Some(_), true) => return Err(usage_error!("--input and --stdin are exclusive")),
+            (Some(path), false) => InputSource::File(PathBuf::from(path)),
+            (None, true) => InputSource::Stdin,
+            (None, false) => InputSource::Default,
+        };
+        if input != InputSource::Default && days.len() != 1 {
+            return Err(usage_error!("an input override requires exactly one day"));
+        }
+
+        Ok(Selection { days, parts, input })
+    }
+
+    /// Return the selected parts for a day
+    ///
+    /// Selecting both parts skips part 2 of a day that has only solved part 1, while asking for
+    /// part 2 explicitly is passed through so that it can be reported as an error.
+    pub fn parts_for(&self, day: u8) -> Vec<Part> {
+        match aoc::info(day) {
+            Some(info) if self.parts.len() > 1 => self
+                .parts
+                .iter()
+                .copied()
+                .filter(|&part| info.has_part(part))
+                .collect(),
+            _ => self.parts.clone(),
+        }
+    }
+
+    /// Return true if the selection consists of a single day and part
+    pub fn is_single(&self) -> bool {
+        self.days.len() == 1 && self.parts_for(self.days[0]).len() == 1
+    }
+}
+
+fn parse_count(value: &str) -> Result<usize, UsageError> {
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(usage_error!("invalid count '{}'", value)),
+    }
+}
+
+fn parse_day(value: &str) -> Result<u8, UsageError> {
+    match value.trim().parse::<u8>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(usage_error!("invalid day '{}'", value)),
+    }
+}
+
+/// Parse a day specification into the sorted list of solved days it selects
+///
+/// Ranges silently skip days that have not been solved, but naming an unsolved day on its own is
+/// an error.
+fn parse_days(spec: &str) -> Result<Vec<u8>, UsageError> {
+    let solved = aoc::days().collect::<Vec<_>>();
+    let mut days = Vec::new();
+
+    for item in spec.split(',') {
+        if item == "all" {
+            days.extend(solved.iter().copied());
+            continue;
+        }
+
+        let range = if let Some((start, end)) = item.split_once("..=") {
+            Some((parse_day(start)?, parse_day(end)?))
+        } else if let Some((start, end)) = item.split_once("..") {
+            // The end of an exclusive range is one past the last day, so `1..26` is valid
+            let last = match end.trim().parse::<u8>() {
+                Ok(end @ 1..=26) => end - 1,
+                _ => return Err(usage_error!("invalid day '{}'", end)),
+            };
+            Some((parse_day(start)?, last))
+        } else if let Some((start, end)) = item.split_once('-') {
+            Some((parse_day(start)?, parse_day(end)?))
+        } else {
+            None
+        };
+
+        match range {
+            Some((start, end)) => {
+                if start > end {
+                    return Err(usage_error!("empty day range '{}'", item));
+                }
+                days.extend((start..=end).filter(|day| solved.contains(day)));
+            }
+            None => {
+                let day = parse_day(item)?;
+                if !solved.contains(&day) {
+                    return Err(usage_error!("day {} has not been solved", day));
+                }
+                days.push(day);
+            }
+        }
+    }
+
+    days.sort();
+    days.dedup();
+    if days.is_empty() {
+        return Err(usage_error!("no solved days in '{}'", spec));
+    }
+    Ok(days)
+}
+
+/// A day's puzzle input, along with its parsed form once a solver has produced it
+///
+/// Solvers that split parsing from solving then parse the input only once for both parts.
+struct DayInput {
+    text: String,
+    parsed: OnceCell<Option<Result<Parsed, AocError>>>,
+}
+
+impl DayInput {
+    fn new(text: String) -> DayInput {
+        DayInput {
+            text,
+            parsed: OnceCell::new(),
+        }
+    }
+}
+
+/// Read the input for a day from the given source
+fn read_input(day: u8, source: &InputSource) -> Result<DayInput, InputError> {
+    read_input_text(day, source).map(DayInput::new)
+}
+
+fn read_input_text(day: u8, source: &InputSource) -> Result<String, InputError> {
+    match source {
+        InputSource::Default => InputLocator::from_env().read(day),
+        InputSource::File(path) => std::fs::read_to_string(path).map_err(|error| InputError::Io {
+            path: path.clone(),
+            error,
+        }),
+        InputSource::Stdin => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|error| InputError::Io {
+                    path: PathBuf::from("<stdin>"),
+                    error,
+                })?;
+            Ok(input)
+        }
+    }
+}
+
+/// Open the input for a day from the given source, to be read as a stream
+fn open_input(day: u8, source: &InputSource) -> Result<Box<dyn BufRead>, InputError> {
+    let path = match source {
+        InputSource::Default => InputLocator::from_env().locate(day)?,
+        InputSource::File(path) => path.clone(),
+        InputSource::Stdin => return Ok(Box::new(io::stdin().lock())),
+    };
+    match File::open(&path) {
+        Ok(file) => Ok(Box::new(BufReader::new(file))),
+        Err(error) => Err(InputError::Io { path, error }),
+    }
+}
+
+/// Return the solver for one part of a day, or an error if the part has not been solved
+fn solver(day: u8, part: Part) -> Result<&'static dyn Aoc, AocError> {
+    aoc::problem(day)
+        .filter(|_| aoc::info(day).is_some_and(|info| info.has_part(part)))
+        .map(|solutions| part.select(solutions))
+        .ok_or_else(|| AocError::new(AocErrorKind::Unsolved).with_context(day, part))
+}
+
+thread_local! {
+    /// Set while `catch_panics` runs a solver on this thread, so that the panic hook leaves the
+    /// report to it
+    static CATCHING_PANICS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Install a panic hook that stays quiet about panics that `catch_panics` turns into errors
+///
+/// Panics anywhere else, or on other threads, still go to the default hook.
+pub fn install_panic_hook() {
+    let default = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !CATCHING_PANICS.get() {
+            default(info);
+        }
+    }));
+}
+
+/// Run `f`, turning a panic into an error
+///
+/// Solvers report bad input through `AocError`, but a panicking solver is also caught so that one
+/// broken day cannot take down a run over many days.
+fn catch_panics<T>(f: impl FnOnce() -> Result<T, AocError>) -> Result<T, AocError> {
+    let was_catching = CATCHING_PANICS.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING_PANICS.set(was_catching);
+
+    result.unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".to_string());
+        Err(AocError::new(AocErrorKind::Panic(message)))
+    })
+}
+
+/// Solve one part of a day, reporting a panicking solver as an error
+fn solve(day: u8, part: Part, input: &DayInput) -> Result<Answer, AocError> {
+    let solver = solver(day, part)?;
+    catch_panics(
+        || match input.parsed.get_or_init(|| solver.try_parse(&input.text)) {
+            Some(Ok(parsed)) => solver.try_solve_parsed(parsed),
+            Some(Err(err)) => Err(err.clone()),
+            None => solver.try_solve(&input.text),
+        },
+    )
+    .map_err(|err| err.with_context(day, part))
+}
+
+/// Solve one part of a day from input read as a stream, reporting a panicking solver as an error
+fn solve_stream(day: u8, part: Part, input: &mut dyn BufRead) -> Result<Answer, AocError> {
+    let solver = solver(day, part)?;
+    catch_panics(|| solver.try_solve_stream(input)).map_err(|err| err.with_context(day, part))
+}
+
+/// Format a solver error for the terminal, showing the offending line of input for parse errors
+fn describe(err: &AocError) -> String {
+    match err.kind() {
+        AocErrorKind::Parse(scan_error) => format!("{}\n{}", err, scan_error.render()),
+        _ => err.to_string(),
+    }
+}
+
+/// Format a duration with a unit suited to its magnitude
+fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos < 1_000 {
+        format!("{} ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.2} µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2} ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2} s", nanos as f64 / 1e9)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclusive_ranges_stop_before_their_end() {
+        let all = parse_days("all").unwrap();
+        assert_eq!(parse_days("1..26").unwrap(), all);
+        assert_eq!(parse_days("1..=25").unwrap(), all);
+        assert_eq!(parse_days("1-25").unwrap(), all);
+        assert_eq!(parse_days("1..3").unwrap(), parse_days("1..=2").unwrap());
+    }
+
+    #[test]
+    fn out_of_range_days_are_rejected() {
+        assert!(parse_days("1..27").is_err());
+        assert!(parse_days("1..=26").is_err());
+        assert!(parse_days("0..5").is_err());
+        assert!(parse_days("3..3").is_err());
+        assert!(parse_days("26").is_err());
+    }
+
+    #[test]
+    fn panics_become_errors() {
+        let err = catch_panics::<()>(|| panic!("bad input {}", 3)).unwrap_err();
+        assert!(matches!(err.kind(), AocErrorKind::Panic(message) if message == "bad input 3"));
+        assert!(!CATCHING_PANICS.get());
+        assert_eq!(catch_panics(|| Ok(5)).unwrap(), 5);
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use super::{
+    describe, format_duration, read_input, solve, solver, Selection, EXIT_FAILURE, EXIT_INPUT,
+    EXIT_SUCCESS,
+};
+use crate::aoc::Part;
+
+/// Number of timed runs per part when none is given on the command line
+pub const DEFAULT_ITERATIONS: usize = 10;
+
+/// Number of untimed runs per part when none is given on the command line
+pub const DEFAULT_WARMUP: usize = 3;
+
+/// Settings for a benchmark run
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Number of timed runs per part
+    pub iterations: usize,
+    /// Number of untimed runs per part before the timed ones
+    pub warmup: usize,
+    /// Print the results as JSON instead of a table
+    pub json: bool,
+}
+
+/// Summary statistics over the timed runs of a part
+#[derive(Debug, Clone, Copy)]
+struct Stats {
+    min: Duration,
+    median: Duration,
+    mean: Duration,
+    stddev: Duration,
+}
+
+impl Stats {
+    fn new(mut samples: Vec<Duration>) -> Stats {
+        samples.sort();
+        let n = samples.len();
+        let median = if n.is_multiple_of(2) {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        } else {
+            samples[n / 2]
+        };
+        let mean = samples.iter().sum::<Duration>() / n as u32;
+        let variance = samples
+            .iter()
+            .map(|sample| (sample.as_secs_f64() - mean.as_secs_f64()).powi(2))
+            .sum::<f64>()
+            / (n.max(2) - 1) as f64;
+
+        Stats {
+            min: samples[0],
+            median,
+            mean,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+
+    fn to_json(self) -> String {
+        format!(
+            "{{\"min_ns\": {}, \"median_ns\": {}, \"mean_ns\": {}, \"stddev_ns\": {}}}",
+            self.min.as_nanos(),
+            self.median.as_nanos(),
+            self.mean.as_nanos(),
+            self.stddev.as_nanos()
+        )
+    }
+}
+
+/// The benchmark results for one part of a day
+struct Measurement {
+    day: u8,
+    part: Part,
+    /// Statistics for parsing and for solving from the parsed input, if the solver splits them
+    phases: Option<(Stats, Stats)>,
+    /// Statistics for a whole solve, including parsing, or the error the solver failed with
+    run: Result<Stats, String>,
+}
+
+/// Time `f` over the warm-up and timed runs and return the statistics of the timed runs
+fn measure<T>(options: &Options, mut f: impl FnMut() -> T) -> Stats {
+    for _ in 0..options.warmup {
+        black_box(f());
+    }
+    let samples = (0..options.iterations)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+    Stats::new(samples)
+}
+
+/// Time the selected days and parts, reading each input only once
+///
+/// Each part is solved once outside the timing to check that it succeeds, then run through the
+/// warm-up and the timed runs. Solvers that split parsing from solving also have each phase timed
+/// on its own.
+pub fn execute(selection: &Selection, options: &Options) -> u8 {
+    let mut code = EXIT_SUCCESS;
+    let mut measurements = Vec::new();
+
+    if !options.json {
+        print_header();
+    }
+    for &day in selection.days.iter() {
+        let input = match read_input(day, &selection.input) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                code = code.max(EXIT_INPUT);
+                continue;
+            }
+        };
+
+        for part in selection.parts_for(day) {
+            let measurement = match solve(day, part, &input).and_then(|_| solver(day, part)) {
+                Ok(solver) => Measurement {
+                    day,
+                    part,
+                    phases: match solver.try_parse(&input.text) {
+                        Some(Ok(parsed)) => Some((
+                            measure(options, || solver.try_parse(black_box(&input.text))),
+                            measure(options, || solver.try_solve_parsed(black_box(&parsed))),
+                        )),
+                        _ => None,
+                    },
+                    run: Ok(measure(options, || {
+                        solver.try_solve(black_box(&input.text))
+                    })),
+                },
+                Err(err) => {
+                    eprintln!("error: {}", describe(&err));
+                    code = code.max(EXIT_FAILURE);
+                    Measurement {
+                        day,
+                        part,
+                        phases: None,
+                        run: Err(err.to_string()),
+                    }
+                }
+            };
+            if !options.json {
+                print_row(&measurement);
+            }
+            measurements.push(measurement);
+        }
+    }
+
+    if options.json {
+        print_json(options, &measurements);
+    } else {
+        print_total(&measurements);
+    }
+    code
+}
+
+/// Sum the statistics of every successful run, for the bottom line of the table
+fn totals(measurements: &[Measurement]) -> (Duration, Duration, Duration) {
+    measurements
+        .iter()
+        .filter_map(|measurement| measurement.run.as_ref().ok())
+        .fold(
+            (Duration::ZERO, Duration::ZERO, Duration::ZERO),
+            |total, stats| {
+                (
+                    total.0 + stats.min,
+                    total.1 + stats.median,
+                    total.2 + stats.mean,
+                )
+            },
+        )
+}
+
+fn print_header() {
+    println!(
+        "Day  Part  {:>10}  {:>10}  {:>10}  {:>10}  {:>10}  {:>10}",
+        "Parse", "Solve", "Min", "Median", "Mean", "Stddev"
+    );
+}
+
+fn print_row(measurement: &Measurement) {
+    let (parse, solve) = match measurement.phases {
+        Some((parse, solve)) => (format_duration(parse.median), format_duration(solve.median)),
+        None => ("-".to_string(), "-".to_string()),
+    };
+    match &measurement.run {
+        Ok(stats) => println!(
+            "{:02}   {:>4}  {:>10}  {:>10}  {:>10}  {:>10}  {:>10}  {:>10}",
+            measurement.day,
+            measurement.part,
+            parse,
+            solve,
+            format_duration(stats.min),
+            format_duration(stats.median),
+            format_duration(stats.mean),
+            format_duration(stats.stddev)
+        ),
+        Err(_) => println!(
+            "{:02}   {:>4}  {:>10}  {:>10}  {:>10}",
+            measurement.day, measurement.part, "-", "-", "FAILED"
+        ),
+    }
+}
+
+fn print_total(measurements: &[Measurement]) {
+    let (min, median, mean) = totals(measurements);
+    println!(
+        "Total       {:>10}  {:>10}  {:>10}  {:>10}  {:>10}",
+        "",
+        "",
+        format_duration(min),
+        format_duration(median),
+        format_duration(mean)
+    );
+}
+
+fn print_json(options: &Options, measurements: &[Measurement]) {
+    let results = measurements
+        .iter()
+        .map(|measurement| {
+            let (parse, solve) = match measurement.phases {
+                Some((parse, solve)) => (parse.to_json(), solve.to_json()),
+                None => ("null".to_string(), "null".to_string()),
+            };
+            let run = match &measurement.run {
+                Ok(stats) => format!("\"run\": {}", stats.to_json()),
+                Err(message) => format!("\"error\": {}", json_string(message)),
+            };
+            format!(
+                "    {{\"day\": {}, \"part\": {}, \"parse\": {}, \"solve\": {}, {}}}",
+                measurement.day, measurement.part, parse, solve, run
+            )
+        })
+        .collect::<Vec<_>>();
+    let (min, median, mean) = totals(measurements);
+
+    println!("{{");
+    println!("  \"iterations\": {},", options.iterations);
+    println!("  \"warmup\": {},", options.warmup);
+    println!("  \"results\": [\n{}\n  ],", results.join(",\n"));
+    println!(
+        "  \"total\": {{\"min_ns\": {}, \"median_ns\": {}, \"mean_ns\": {}}}",
+        min.as_nanos(),
+        median.as_nanos(),
+        mean.as_nanos()
+    );
+    println!("}}");
+}
+
+/// Quote a string for JSON output
+fn json_string(value: &str) -> String {
+    let mut result = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().copied().map(Duration::from_millis).collect()
+    }
+
+    /// Assert that two durations are within a nanosecond, allowing for rounding through `f64`
+    fn assert_close(actual: Duration, expected: Duration) {
+        assert!(
+            actual.abs_diff(expected) <= Duration::from_nanos(1),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn stats_odd_samples() {
+        let stats = Stats::new(millis(&[5, 1, 3]));
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.mean, Duration::from_millis(3));
+        assert_close(stats.stddev, Duration::from_millis(2));
+    }
+
+    #[test]
+    fn stats_even_samples() {
+        let stats = Stats::new(millis(&[4, 1, 2, 3]));
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_micros(2500));
+        assert_eq!(stats.mean, Duration::from_micros(2500));
+        assert_close(
+            stats.stddev,
+            Duration::from_secs_f64(0.001 * (5.0f64 / 3.0).sqrt()),
+        );
+    }
+
+    #[test]
+    fn stats_single_sample() {
+        let stats = Stats::new(millis(&[7]));
+        assert_eq!(stats.median, Duration::from_millis(7));
+        assert_eq!(stats.stddev, Duration::ZERO);
+        assert_eq!(
+            stats.to_json(),
+            "{\"min_ns\": 7000000, \"median_ns\": 7000000, \"mean_ns\": 7000000, \"stddev_ns\": 0}"
+        );
+    }
+
+    #[test]
+    fn json_strings() {
+        assert_eq!(json_string("day 1"), "\"day 1\"");
+        assert_eq!(json_string("a \"b\" \\ c"), "\"a \\\"b\\\" \\\\ c\"");
+        assert_eq!(json_string("x\ny\tz\u{7f}"), "\"x\\ny\\u0009z\\u007f\"");
+        assert_eq!(json_string("é"), "\"é\"");
+    }
+}
@@ -0,0 +1,11 @@
+use super::EXIT_SUCCESS;
+use crate::aoc;
+
+/// Print every day that has solutions, along with the parts that have been solved
+pub fn execute() -> u8 {
+    for info in aoc::PROBLEMS.iter() {
+        let parts = if info.has_part2 { "1, 2" } else { "1" };
+        println!("Day {:02}  {:<8} parts {}", info.day, info.module, parts);
+    }
+    EXIT_SUCCESS
+}
@@ -0,0 +1,176 @@
+use std::{fs::OpenOptions, io::Write, path::Path};
+
+use super::{EXIT_FAILURE, EXIT_SUCCESS};
+
+const TEMPLATE: &str = include_str!("../../template/problem.rs");
+
+/// Create the files for a new day: its solution from the problem template, an empty input and an
+/// example whose answers are left to fill in
+///
+/// If `from` names a saved copy of the puzzle description, as HTML or markdown, its first code
+/// block becomes the input of the example. The example's test is ignored until its answers are
+/// filled in. Nothing is created if the solution or the example already exists, and an existing
+/// input is left alone.
+pub fn execute(day: u8, from: Option<&Path>) -> u8 {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let source_path = root.join(format!("src/aoc/problems/day{:02}.rs", day));
+    let example_path = root.join(format!("examples/day{:02}/example.txt", day));
+    let input_path = root.join(format!("inputs/day{:02}.txt", day));
+
+    for path in [&source_path, &example_path] {
+        if path.exists() {
+            eprintln!("error: {} already exists", path.display());
+            return EXIT_FAILURE;
+        }
+    }
+
+    let example_input = match from {
+        Some(from) => match std::fs::read_to_string(from) {
+            Ok(description) => match extract_example(&description) {
+                Some(example) => example,
+                None => {
+                    eprintln!("error: no code block found in {}", from.display());
+                    return EXIT_FAILURE;
+                }
+            },
+            Err(err) => {
+                eprintln!("error: could not read {}: {}", from.display(), err);
+                return EXIT_FAILURE;
+            }
+        },
+        None => String::new(),
+    };
+    let source = TEMPLATE.replace("{{day}}", &day.to_string());
+    let example = format!("# part1 = \n# part2 = \n---\n{}", example_input);
+
+    let mut files = vec![(source_path, source), (example_path, example)];
+    if input_path.exists() {
+        println!("Keeping {}", input_path.display());
+    } else {
+        files.push((input_path, String::new()));
+    }
+    for (path, contents) in files {
+        if let Err(err) = create_new(&path, &contents) {
+            eprintln!("error: could not create {}: {}", path.display(), err);
+            return EXIT_FAILURE;
+        }
+        println!("Created {}", path.display());
+    }
+    EXIT_SUCCESS
+}
+
+/// Write a file along with its parent directories, failing if the file already exists
+fn create_new(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // `create_new` refuses to clobber a day that has already been started
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+}
+
+/// Return the contents of the first code block in a puzzle description
+///
+/// HTML descriptions, as saved from the puzzle page, have their examples in `<pre><code>` blocks
+/// with markup such as `<em>` inside, while markdown ones use fenced blocks.
+fn extract_example(description: &str) -> Option<String> {
+    let example = if let Some((_, rest)) = description.split_once("<pre><code>") {
+        let (block, _) = rest.split_once("</code></pre>")?;
+        decode_html(&strip_tags(block))
+    } else {
+        let (_, rest) = description.split_once("```")?;
+        // Skip the language tag on the opening fence
+        let (_, rest) = rest.split_once('\n')?;
+        let (block, _) = rest.split_once("```")?;
+        block.to_string()
+    };
+
+    if example.ends_with('\n') {
+        Some(example)
+    } else {
+        Some(example + "\n")
+    }
+}
+
+/// Remove every HTML tag from a string
+fn strip_tags(html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Replace the HTML entities that appear in puzzle examples with the characters they stand for
+fn decode_html(html: &str) -> String {
+    [
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&quot;", "\""),
+        ("&#39;", "'"),
+        ("&amp;", "&"),
+    ]
+    .iter()
+    .fold(html.to_string(), |text, (entity, c)| {
+        text.replace(entity, c)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE_PAGE: &str = r#"<article class="day-desc"><h2>--- Day 1: Trebuchet?! ---</h2>
+<p>For example:</p>
+<pre><code>1abc2
+<em>pqr3stu8vwx</em>
+a1b<code><em>2</em></code>c3d4e5f
+x &lt;y&gt; &amp;&amp; &quot;z&quot; &#39;w&#39; &amp;lt;
+</code></pre>
+<p>In this example, the calibration values are <code>12</code> and <code>38</code>.</p>
+<pre><code>second
+</code></pre>
+</article>"#;
+
+    #[test]
+    fn example_from_html() {
+        assert_eq!(
+            extract_example(PUZZLE_PAGE).unwrap(),
+            "1abc2\npqr3stu8vwx\na1b2c3d4e5f\nx <y> && \"z\" 'w' &lt;\n"
+        );
+        assert_eq!(
+            extract_example("<pre><code>abc</code></pre>").unwrap(),
+            "abc\n"
+        );
+        assert_eq!(extract_example("<pre><code>abc\n"), None);
+    }
+
+    #[test]
+    fn example_from_markdown() {
+        let description = "For example:\n\n```text\nLR\n\n11A = (11B, XXX)\n```\n\nMore.\n";
+        assert_eq!(
+            extract_example(description).unwrap(),
+            "LR\n\n11A = (11B, XXX)\n"
+        );
+        assert_eq!(extract_example("```\nabc"), None);
+        assert_eq!(extract_example("no example here"), None);
+    }
+
+    #[test]
+    fn tags_and_entities() {
+        assert_eq!(strip_tags("<p>a <code><em>b</em></code> c</p>"), "a b c");
+        assert_eq!(strip_tags("x > y"), "x > y");
+        assert_eq!(decode_html("&lt;&amp;&gt;"), "<&>");
+        // An escaped entity decodes to the entity's text, not to the character
+        assert_eq!(decode_html("&amp;quot;"), "&quot;");
+    }
+}
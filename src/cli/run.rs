@@ -0,0 +1,123 @@
+use std::{path::PathBuf, rc::Rc};
+
+use super::{
+    describe, open_input, read_input, solve, solve_stream, Selection, EXIT_FAILURE, EXIT_INPUT,
+    EXIT_SUCCESS,
+};
+use crate::{
+    aoc::{Answer, AocError, Part},
+    util::scanner::trace::{self, AnnotatedInput, TraceSink, WriteSink},
+};
+
+/// Where `run --trace` sends what the parsers scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trace {
+    /// Print each scan event to standard error
+    Stderr,
+    /// Print the input to standard error with the scanned tokens marked under it
+    Annotate,
+    /// Write each scan event to a file
+    File(PathBuf),
+}
+
+impl Trace {
+    pub fn parse(value: &str) -> Trace {
+        match value {
+            "stderr" => Trace::Stderr,
+            "annotate" => Trace::Annotate,
+            path => Trace::File(PathBuf::from(path)),
+        }
+    }
+}
+
+/// Solve the selected days and parts and print their answers
+///
+/// A single day and part prints just the answer so that scripts can capture it directly. Multi-line
+/// answers start on the line after their label so that they stay aligned. Traces go to standard
+/// error or a file, so that they never mix with the answers. With `stream`, each part reads its
+/// input afresh as it solves, and solvers that can read a line at a time never hold all of it.
+pub fn execute(selection: &Selection, trace: Option<&Trace>, stream: bool) -> u8 {
+    if stream {
+        return execute_streamed(selection);
+    }
+
+    let mut code = EXIT_SUCCESS;
+    let annotated = Rc::new(AnnotatedInput::new());
+    let sink: Option<Rc<dyn TraceSink>> = match trace {
+        None => None,
+        Some(Trace::Stderr) => Some(Rc::new(WriteSink::stderr())),
+        Some(Trace::Annotate) => Some(annotated.clone()),
+        Some(Trace::File(path)) => match WriteSink::create(path) {
+            Ok(sink) => Some(Rc::new(sink)),
+            Err(err) => {
+                eprintln!("error: could not create {}: {}", path.display(), err);
+                return EXIT_FAILURE;
+            }
+        },
+    };
+
+    for &day in selection.days.iter() {
+        let input = match read_input(day, &selection.input) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                code = code.max(EXIT_INPUT);
+                continue;
+            }
+        };
+
+        for part in selection.parts_for(day) {
+            let result = match &sink {
+                Some(sink) => trace::with_sink(sink.clone(), || solve(day, part, &input)),
+                None => solve(day, part, &input),
+            };
+            code = code.max(report(selection, day, part, result));
+        }
+
+        if trace == Some(&Trace::Annotate) {
+            eprint!("{}", annotated.render(&input.text));
+        }
+    }
+
+    code
+}
+
+/// Solve the selected days and parts with each part reading its input as a stream
+fn execute_streamed(selection: &Selection) -> u8 {
+    let mut code = EXIT_SUCCESS;
+    for &day in selection.days.iter() {
+        for part in selection.parts_for(day) {
+            let mut input = match open_input(day, &selection.input) {
+                Ok(input) => input,
+                Err(err) => {
+                    eprintln!("error: {}", err);
+                    code = code.max(EXIT_INPUT);
+                    break;
+                }
+            };
+            code = code.max(report(
+                selection,
+                day,
+                part,
+                solve_stream(day, part, &mut input),
+            ));
+        }
+    }
+    code
+}
+
+/// Print the answer to one part, or its error, and return the exit code it calls for
+fn report(selection: &Selection, day: u8, part: Part, result: Result<Answer, AocError>) -> u8 {
+    match result {
+        Ok(answer) if selection.is_single() => println!("{}", answer),
+        Ok(answer) if answer.is_multiline() => {
+            println!("Day {:02}, part {}:\n{}", day, part, answer)
+        }
+        Ok(answer) => println!("Day {:02}, part {}: {}", day, part, answer),
+        Err(err) => {
+            eprintln!("error: {}", describe(&err));
+            return EXIT_FAILURE;
+        }
+    }
+    EXIT_SUCCESS
+}
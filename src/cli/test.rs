@@ -0,0 +1,77 @@
+use std::time::Instant;
+
+use super::{
+    describe, format_duration, read_input, solve, InputSource, Selection, EXIT_FAILURE, EXIT_INPUT,
+    EXIT_SUCCESS,
+};
+use crate::aoc::answers::{AnswerStore, DayAnswers};
+
+/// Run the selected days and parts against their inputs and report which of them fail
+///
+/// Answers for the default inputs are checked against the answer store, and a wrong answer fails
+/// like an error does. Answers with nothing to check against are reported as unchecked.
+pub fn execute(selection: &Selection) -> u8 {
+    let store = AnswerStore::default();
+    let mut code = EXIT_SUCCESS;
+    let mut failed = 0;
+    let mut passed = 0;
+    let mut unchecked = 0;
+
+    for &day in selection.days.iter() {
+        // Known answers only hold for the day's own input
+        let known = match &selection.input {
+            InputSource::Default => match store.load(day) {
+                Ok(known) => known,
+                Err(err) => {
+                    eprintln!("error: {}", err);
+                    code = code.max(EXIT_INPUT);
+                    continue;
+                }
+            },
+            _ => DayAnswers::default(),
+        };
+        let input = match read_input(day, &selection.input) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                code = code.max(EXIT_INPUT);
+                continue;
+            }
+        };
+
+        for part in selection.parts_for(day) {
+            let start = Instant::now();
+            let result = solve(day, part, &input);
+            let elapsed = format_duration(start.elapsed());
+            match (result, known.get(part)) {
+                (Ok(answer), Some(expected)) if answer.to_string() == expected => {
+                    passed += 1;
+                    println!("Day {:02}, part {}: ok ({})", day, part, elapsed);
+                }
+                (Ok(answer), Some(expected)) => {
+                    failed += 1;
+                    code = code.max(EXIT_FAILURE);
+                    println!("Day {:02}, part {}: WRONG ANSWER", day, part);
+                    println!("expected: {}", expected);
+                    println!("  actual: {}", answer);
+                }
+                (Ok(_), None) => {
+                    unchecked += 1;
+                    println!("Day {:02}, part {}: unchecked ({})", day, part, elapsed);
+                }
+                (Err(err), _) => {
+                    failed += 1;
+                    code = code.max(EXIT_FAILURE);
+                    println!("Day {:02}, part {}: FAILED", day, part);
+                    println!("{}", describe(&err));
+                }
+            }
+        }
+    }
+
+    println!(
+        "{} passed, {} failed, {} unchecked",
+        passed, failed, unchecked
+    );
+    code
+}
@@ -0,0 +1,156 @@
+use std::io::{BufRead, Write};
+
+use super::{describe, read_input, solve, Selection, EXIT_FAILURE, EXIT_INPUT, EXIT_SUCCESS};
+use crate::aoc::{
+    answers::{AnswerStore, DayAnswers},
+    Part,
+};
+
+/// The outcome of checking one part against its known answer
+enum Outcome {
+    /// The answer matches the known answer
+    Match,
+    /// The answer differs from the known answer
+    Mismatch { expected: String, actual: String },
+    /// There is no known answer to compare with
+    Unrecorded(String),
+    /// The solver failed
+    Failed(String),
+}
+
+/// Solve the selected days and parts and compare the answers with the answer store
+///
+/// With `record`, answers that are new or differ from the store are written back to it once the
+/// user has confirmed them.
+pub fn execute(selection: &Selection, record: bool) -> u8 {
+    let store = AnswerStore::default();
+    let mut code = EXIT_SUCCESS;
+    let mut updates = Vec::<(u8, DayAnswers)>::new();
+    let mut counts = [0; 4];
+
+    println!("  Day  Part  Result");
+    for &day in selection.days.iter() {
+        let mut known = match store.load(day) {
+            Ok(known) => known,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                code = code.max(EXIT_INPUT);
+                continue;
+            }
+        };
+        let input = match read_input(day, &selection.input) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                code = code.max(EXIT_INPUT);
+                continue;
+            }
+        };
+
+        let mut changed = false;
+        for part in selection.parts_for(day) {
+            let outcome = match (solve(day, part, &input), known.get(part)) {
+                (Err(err), _) => Outcome::Failed(describe(&err)),
+                (Ok(answer), None) => Outcome::Unrecorded(answer.to_string()),
+                (Ok(answer), Some(expected)) if answer.to_string() == expected => Outcome::Match,
+                (Ok(answer), Some(expected)) => Outcome::Mismatch {
+                    expected: expected.to_string(),
+                    actual: answer.to_string(),
+                },
+            };
+            print_outcome(day, part, &outcome);
+
+            match outcome {
+                Outcome::Match => counts[0] += 1,
+                Outcome::Mismatch { actual, .. } => {
+                    counts[1] += 1;
+                    known.set(part, actual);
+                    changed = true;
+                }
+                Outcome::Unrecorded(actual) => {
+                    counts[2] += 1;
+                    known.set(part, actual);
+                    changed = true;
+                }
+                Outcome::Failed(_) => {
+                    counts[3] += 1;
+                    code = code.max(EXIT_FAILURE);
+                }
+            }
+        }
+        if changed {
+            updates.push((day, known));
+        }
+    }
+
+    println!(
+        "{} matched, {} mismatched, {} unrecorded, {} failed",
+        counts[0], counts[1], counts[2], counts[3]
+    );
+
+    // Mismatches are failures until they are recorded, while missing inputs and failed solvers are
+    // failures either way
+    let mismatch_code = if counts[1] > 0 {
+        EXIT_FAILURE
+    } else {
+        EXIT_SUCCESS
+    };
+    if record && !updates.is_empty() {
+        if !confirm(&format!(
+            "Record the new answers for {} day(s)?",
+            updates.len()
+        )) {
+            println!("Nothing recorded");
+            return code.max(mismatch_code);
+        }
+        for (day, answers) in updates.iter() {
+            if let Err(err) = store.save(*day, answers) {
+                eprintln!("error: {}", err);
+                return code.max(EXIT_FAILURE);
+            }
+            println!("Recorded {}", store.path(*day).display());
+        }
+        return code;
+    }
+
+    code.max(mismatch_code)
+}
+
+/// Print one row of the result table, with the differing answers as `-` and `+` lines below it
+fn print_outcome(day: u8, part: Part, outcome: &Outcome) {
+    let (marker, status) = match outcome {
+        Outcome::Match => (' ', "ok"),
+        Outcome::Mismatch { .. } => ('!', "MISMATCH"),
+        Outcome::Unrecorded(_) => ('?', "unrecorded"),
+        Outcome::Failed(_) => ('!', "FAILED"),
+    };
+    println!("{} {:02}   {}     {}", marker, day, part, status);
+
+    let print_lines = |prefix: char, text: &str| {
+        for line in text.lines() {
+            println!("              {} {}", prefix, line);
+        }
+    };
+    match outcome {
+        Outcome::Match => {}
+        Outcome::Mismatch { expected, actual } => {
+            print_lines('-', expected);
+            print_lines('+', actual);
+        }
+        Outcome::Unrecorded(actual) => print_lines('+', actual),
+        Outcome::Failed(message) => print_lines(' ', message),
+    }
+}
+
+/// Ask a yes/no question on the terminal, defaulting to no
+fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    if std::io::stdout().flush().is_err() {
+        return false;
+    }
+    let mut reply = String::new();
+    match std::io::stdin().lock().read_line(&mut reply) {
+        Ok(_) => matches!(reply.trim(), "y" | "Y" | "yes"),
+        Err(_) => false,
+    }
+}
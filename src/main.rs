@@ -1,17 +1,18 @@
+use std::process::ExitCode;
+
 mod aoc;
+mod cli;
 mod util;
 
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    cli::install_panic_hook();
 
-    if args.len() != 3 {
-        eprintln!("Usage: aoc <day> <stage>");
-        std::process::exit(1);
+    match cli::Command::parse(&args) {
+        Ok(command) => command.execute(),
+        Err(err) => {
+            eprintln!("error: {}\nRun `aoc help` for usage.", err);
+            ExitCode::from(cli::EXIT_USAGE)
+        }
     }
-
-    let day = args[1].parse::<u8>().expect("Invalid day");
-    let stage = args[2].parse::<u8>().expect("Invalid stage");
-    let input = std::fs::read_to_string(format!("inputs/day{}.txt", day)).unwrap();
-
-    println!("{:08}", aoc::solve_problem(day, stage, &input));
 }
@@ -1 +1,4 @@
+pub mod dir;
+pub mod grid;
 pub mod scanner;
+pub mod search;
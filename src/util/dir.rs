@@ -0,0 +1,328 @@
+//! Compass directions for walking around grids, with `y` growing downward as in `util::grid`
+
+use nalgebra_glm::IVec2;
+
+/// One of the four directions along the grid axes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir4 {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir4 {
+    /// Every direction, clockwise from north
+    pub const ALL: [Dir4; 4] = [Dir4::North, Dir4::East, Dir4::South, Dir4::West];
+
+    /// Iterate over every direction, clockwise from north
+    pub fn all() -> impl Iterator<Item = Dir4> {
+        Dir4::ALL.into_iter()
+    }
+
+    /// Return the direction for a character, or None if it is not one
+    ///
+    /// Arrows (`^>v<`), compass points (`NESW`) and moves (`URDL`) are all accepted.
+    pub fn from_char(c: char) -> Option<Dir4> {
+        match c {
+            '^' | 'N' | 'U' => Some(Dir4::North),
+            '>' | 'E' | 'R' => Some(Dir4::East),
+            'v' | 'S' | 'D' => Some(Dir4::South),
+            '<' | 'W' | 'L' => Some(Dir4::West),
+            _ => None,
+        }
+    }
+
+    /// Return the arrow that points this way
+    pub fn arrow(self) -> char {
+        match self {
+            Dir4::North => '^',
+            Dir4::East => '>',
+            Dir4::South => 'v',
+            Dir4::West => '<',
+        }
+    }
+
+    /// Return the offset of one step this way
+    pub fn delta(self) -> IVec2 {
+        match self {
+            Dir4::North => IVec2::new(0, -1),
+            Dir4::East => IVec2::new(1, 0),
+            Dir4::South => IVec2::new(0, 1),
+            Dir4::West => IVec2::new(-1, 0),
+        }
+    }
+
+    /// Return the direction of a single step, or None if `delta` is not one
+    pub fn from_delta(delta: IVec2) -> Option<Dir4> {
+        Dir4::all().find(|dir| dir.delta() == delta)
+    }
+
+    /// Return the direction turned by a number of quarter turns, clockwise if positive
+    pub fn rotate(self, quarter_turns: i32) -> Dir4 {
+        // Reduce the turns first so that adding them cannot overflow
+        Dir4::ALL[(self as usize + quarter_turns.rem_euclid(4) as usize) % 4]
+    }
+
+    /// Return the direction a quarter turn clockwise from this one
+    pub fn turn_right(self) -> Dir4 {
+        self.rotate(1)
+    }
+
+    /// Return the direction a quarter turn counterclockwise from this one
+    pub fn turn_left(self) -> Dir4 {
+        self.rotate(-1)
+    }
+
+    /// Return the direction pointing the other way
+    pub fn opposite(self) -> Dir4 {
+        self.rotate(2)
+    }
+}
+
+impl From<Dir4> for IVec2 {
+    fn from(dir: Dir4) -> IVec2 {
+        dir.delta()
+    }
+}
+
+/// One of the eight directions along the grid axes and diagonals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Dir8 {
+    /// Every direction, clockwise from north
+    pub const ALL: [Dir8; 8] = [
+        Dir8::North,
+        Dir8::NorthEast,
+        Dir8::East,
+        Dir8::SouthEast,
+        Dir8::South,
+        Dir8::SouthWest,
+        Dir8::West,
+        Dir8::NorthWest,
+    ];
+
+    /// Iterate over every direction, clockwise from north
+    pub fn all() -> impl Iterator<Item = Dir8> {
+        Dir8::ALL.into_iter()
+    }
+
+    /// Return the offset of one step this way
+    pub fn delta(self) -> IVec2 {
+        match self {
+            Dir8::North => IVec2::new(0, -1),
+            Dir8::NorthEast => IVec2::new(1, -1),
+            Dir8::East => IVec2::new(1, 0),
+            Dir8::SouthEast => IVec2::new(1, 1),
+            Dir8::South => IVec2::new(0, 1),
+            Dir8::SouthWest => IVec2::new(-1, 1),
+            Dir8::West => IVec2::new(-1, 0),
+            Dir8::NorthWest => IVec2::new(-1, -1),
+        }
+    }
+
+    /// Return the direction of a single step, or None if `delta` is not one
+    pub fn from_delta(delta: IVec2) -> Option<Dir8> {
+        Dir8::all().find(|dir| dir.delta() == delta)
+    }
+
+    /// Return the direction turned by a number of eighth turns, clockwise if positive
+    pub fn rotate_eighths(self, eighth_turns: i32) -> Dir8 {
+        Dir8::ALL[(self as usize + eighth_turns.rem_euclid(8) as usize) % 8]
+    }
+
+    /// Return the direction turned by a number of quarter turns, clockwise if positive
+    pub fn rotate(self, quarter_turns: i32) -> Dir8 {
+        self.rotate_eighths(quarter_turns.rem_euclid(4) * 2)
+    }
+
+    /// Return the direction a quarter turn clockwise from this one
+    pub fn turn_right(self) -> Dir8 {
+        self.rotate(1)
+    }
+
+    /// Return the direction a quarter turn counterclockwise from this one
+    pub fn turn_left(self) -> Dir8 {
+        self.rotate(-1)
+    }
+
+    /// Return the direction pointing the other way
+    pub fn opposite(self) -> Dir8 {
+        self.rotate(2)
+    }
+
+    /// Return true for the four directions between the axes
+    pub fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+
+    /// Return the same direction as a `Dir4`, or None if it is diagonal
+    pub fn to_dir4(self) -> Option<Dir4> {
+        (!self.is_diagonal()).then(|| Dir4::ALL[self as usize / 2])
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(dir: Dir4) -> Dir8 {
+        Dir8::ALL[dir as usize * 2]
+    }
+}
+
+impl From<Dir8> for IVec2 {
+    fn from(dir: Dir8) -> IVec2 {
+        dir.delta()
+    }
+}
+
+/// A position and a facing, for walking around a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cursor {
+    pub pos: IVec2,
+    pub dir: Dir4,
+}
+
+impl Cursor {
+    pub fn new(pos: IVec2, dir: Dir4) -> Cursor {
+        Cursor { pos, dir }
+    }
+
+    /// Return the position one step ahead
+    pub fn ahead(&self) -> IVec2 {
+        self.pos + self.dir.delta()
+    }
+
+    /// Move one step ahead and return the new position
+    pub fn step(&mut self) -> IVec2 {
+        self.pos = self.ahead();
+        self.pos
+    }
+
+    /// Turn a quarter turn clockwise
+    #[allow(unused)]
+    pub fn turn_right(&mut self) {
+        self.dir = self.dir.turn_right();
+    }
+
+    /// Turn a quarter turn counterclockwise
+    #[allow(unused)]
+    pub fn turn_left(&mut self) {
+        self.dir = self.dir.turn_left();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_any_number_of_turns() {
+        assert_eq!(Dir4::North.rotate(0), Dir4::North);
+        assert_eq!(Dir4::North.rotate(-1), Dir4::West);
+        assert_eq!(Dir4::East.rotate(-6), Dir4::West);
+        assert_eq!(Dir4::South.rotate(9), Dir4::West);
+        assert_eq!(Dir4::West.rotate(-401), Dir4::South);
+        assert_eq!(Dir4::North.rotate(i32::MAX), Dir4::West);
+        assert_eq!(Dir4::North.rotate(i32::MIN), Dir4::North);
+        assert_eq!(Dir4::West.rotate(i32::MAX), Dir4::South);
+
+        assert_eq!(Dir8::North.rotate_eighths(-1), Dir8::NorthWest);
+        assert_eq!(Dir8::NorthEast.rotate_eighths(17), Dir8::East);
+        assert_eq!(Dir8::SouthWest.rotate(-3), Dir8::NorthWest);
+        assert_eq!(Dir8::East.rotate(10), Dir8::West);
+        assert_eq!(Dir8::West.rotate(i32::MAX), Dir8::South);
+        assert_eq!(Dir8::NorthWest.rotate_eighths(i32::MIN), Dir8::NorthWest);
+    }
+
+    #[test]
+    fn turns() {
+        for dir in Dir4::all() {
+            assert_eq!(dir.turn_right().turn_left(), dir);
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.delta() + dir.opposite().delta(), IVec2::zeros());
+            assert_eq!(dir.turn_right(), dir.rotate(1));
+        }
+        for dir in Dir8::all() {
+            assert_eq!(dir.turn_right().turn_left(), dir);
+            assert_eq!(dir.delta() + dir.opposite().delta(), IVec2::zeros());
+            assert_eq!(dir.turn_right().is_diagonal(), dir.is_diagonal());
+        }
+        assert_eq!(Dir4::North.turn_right(), Dir4::East);
+        assert_eq!(Dir8::NorthEast.turn_left(), Dir8::NorthWest);
+    }
+
+    #[test]
+    fn dir8_round_trips() {
+        for dir in Dir4::all() {
+            let dir8 = Dir8::from(dir);
+            assert!(!dir8.is_diagonal());
+            assert_eq!(dir8.to_dir4(), Some(dir));
+            assert_eq!(dir8.delta(), dir.delta());
+            assert_eq!(dir8.rotate(1).to_dir4(), Some(dir.rotate(1)));
+        }
+        let diagonals = Dir8::all()
+            .filter(|dir| dir.is_diagonal())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagonals,
+            [
+                Dir8::NorthEast,
+                Dir8::SouthEast,
+                Dir8::SouthWest,
+                Dir8::NorthWest
+            ]
+        );
+        assert!(diagonals.iter().all(|dir| dir.to_dir4().is_none()));
+    }
+
+    #[test]
+    fn deltas() {
+        for dir in Dir4::all() {
+            assert_eq!(Dir4::from_delta(dir.delta()), Some(dir));
+            assert_eq!(IVec2::from(dir), dir.delta());
+        }
+        for dir in Dir8::all() {
+            assert_eq!(Dir8::from_delta(dir.delta()), Some(dir));
+            assert_eq!(IVec2::from(dir), dir.delta());
+        }
+        assert_eq!(Dir4::from_delta(IVec2::new(1, 1)), None);
+        assert_eq!(Dir8::from_delta(IVec2::new(0, 2)), None);
+        assert_eq!(Dir8::from_delta(IVec2::zeros()), None);
+        assert_eq!(Dir4::South.delta(), IVec2::new(0, 1));
+    }
+
+    #[test]
+    fn characters() {
+        for alphabet in ["^>v<", "NESW", "URDL"] {
+            let dirs = alphabet.chars().map(Dir4::from_char).collect::<Vec<_>>();
+            assert_eq!(dirs, Dir4::ALL.map(Some), "{}", alphabet);
+        }
+        for c in ['n', 'u', 'V', 'x', ' ', 'A'] {
+            assert_eq!(Dir4::from_char(c), None, "{:?}", c);
+        }
+        for dir in Dir4::all() {
+            assert_eq!(Dir4::from_char(dir.arrow()), Some(dir));
+        }
+    }
+
+    #[test]
+    fn cursor() {
+        let mut cursor = Cursor::new(IVec2::new(2, 2), Dir4::North);
+        assert_eq!(cursor.ahead(), IVec2::new(2, 1));
+        assert_eq!(cursor.step(), IVec2::new(2, 1));
+        cursor.turn_left();
+        cursor.step();
+        cursor.turn_right();
+        cursor.turn_right();
+        assert_eq!(cursor, Cursor::new(IVec2::new(1, 1), Dir4::East));
+    }
+}
@@ -0,0 +1,472 @@
+//! A rectangular grid of cells addressed by `IVec2` positions
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use nalgebra_glm::IVec2;
+
+use super::dir::{Dir4, Dir8};
+
+/// A rectangular grid of cells stored row by row, with `x` growing to the right and `y` downward
+///
+/// Access through `get` and `get_mut` is checked, and returns None outside the grid. Indexing with
+/// an `IVec2` panics outside the grid instead.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: i32,
+    height: i32,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Create a grid with every cell set to `value`
+    pub fn new(width: i32, height: i32, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(width, height, |_| value.clone())
+    }
+
+    /// Create a grid with each cell set to the value `f` returns for its position
+    pub fn from_fn(width: i32, height: i32, mut f: impl FnMut(IVec2) -> T) -> Grid<T> {
+        assert!(width >= 0 && height >= 0, "grid size must not be negative");
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| IVec2::new(x, y)))
+            .map(&mut f)
+            .collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Create a grid from its cells, row by row
+    ///
+    /// Panics if the number of cells is not a multiple of the width.
+    pub fn from_cells(width: i32, cells: Vec<T>) -> Grid<T> {
+        assert!(
+            width > 0 && cells.len().is_multiple_of(width as usize),
+            "{} cells do not make rows of {}",
+            cells.len(),
+            width
+        );
+        Grid {
+            width,
+            height: (cells.len() / width as usize) as i32,
+            cells,
+        }
+    }
+
+    /// Return the number of cells in each row
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Return the number of rows
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Return the width and height as a vector
+    pub fn size(&self) -> IVec2 {
+        IVec2::new(self.width, self.height)
+    }
+
+    /// Return true if a position is inside the grid
+    pub fn contains(&self, pos: IVec2) -> bool {
+        (0..self.width).contains(&pos.x) && (0..self.height).contains(&pos.y)
+    }
+
+    fn index_of(&self, pos: IVec2) -> Option<usize> {
+        self.contains(pos)
+            .then(|| (pos.y * self.width + pos.x) as usize)
+    }
+
+    fn pos_of(&self, index: usize) -> IVec2 {
+        IVec2::new(index as i32 % self.width, index as i32 / self.width)
+    }
+
+    /// Return the cell at a position, or None if it is outside the grid
+    pub fn get(&self, pos: IVec2) -> Option<&T> {
+        self.index_of(pos).map(|index| &self.cells[index])
+    }
+
+    /// Return the cell at a position for changing, or None if it is outside the grid
+    pub fn get_mut(&mut self, pos: IVec2) -> Option<&mut T> {
+        self.index_of(pos).map(|index| &mut self.cells[index])
+    }
+
+    /// Return the cells row by row
+    #[allow(unused)]
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    /// Iterate over the cells row by row, along with their positions
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| (self.pos_of(index), cell))
+    }
+
+    /// Iterate over the cells row by row, along with their positions, for changing
+    #[allow(unused)]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (IVec2, &mut T)> {
+        let width = self.width;
+        self.cells.iter_mut().enumerate().map(move |(index, cell)| {
+            (IVec2::new(index as i32 % width, index as i32 / width), cell)
+        })
+    }
+
+    /// Return one row, or None if it is outside the grid
+    #[allow(unused)]
+    pub fn row(&self, y: i32) -> Option<&[T]> {
+        (0..self.height).contains(&y).then(|| {
+            let start = (y * self.width) as usize;
+            &self.cells[start..start + self.width as usize]
+        })
+    }
+
+    /// Iterate over the rows from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` rejects a chunk size of zero, so an empty grid has no rows to split
+        self.cells.chunks(self.width.max(1) as usize)
+    }
+
+    /// Iterate over the cells of one column from top to bottom, which is empty outside the grid
+    pub fn column(&self, x: i32) -> impl Iterator<Item = &T> {
+        let height = if (0..self.width).contains(&x) {
+            self.height
+        } else {
+            0
+        };
+        (0..height).map(move |y| &self.cells[(y * self.width + x) as usize])
+    }
+
+    /// Iterate over the columns from left to right
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Return the position of the first cell, row by row, that satisfies a predicate
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<IVec2> {
+        self.cells
+            .iter()
+            .position(predicate)
+            .map(|index| self.pos_of(index))
+    }
+
+    /// Iterate over the positions of the cells, row by row, that satisfy a predicate
+    pub fn positions<'g>(
+        &'g self,
+        predicate: impl Fn(&T) -> bool + 'g,
+    ) -> impl Iterator<Item = IVec2> + 'g {
+        self.iter()
+            .filter(move |(_, cell)| predicate(cell))
+            .map(|(pos, _)| pos)
+    }
+
+    fn neighbours<'g>(
+        &'g self,
+        pos: IVec2,
+        deltas: impl Iterator<Item = IVec2> + 'g,
+    ) -> impl Iterator<Item = (IVec2, &'g T)> {
+        deltas.filter_map(move |delta| {
+            let next = pos + delta;
+            self.get(next).map(|cell| (next, cell))
+        })
+    }
+
+    /// Iterate over the cells that share an edge with a position, clockwise from north
+    ///
+    /// Neighbours outside the grid are skipped.
+    pub fn neighbours4(&self, pos: IVec2) -> impl Iterator<Item = (IVec2, &T)> {
+        self.neighbours(pos, Dir4::all().map(Dir4::delta))
+    }
+
+    /// Iterate over the cells around a position, including diagonals, clockwise from north
+    ///
+    /// Neighbours outside the grid are skipped.
+    #[allow(unused)]
+    pub fn neighbours8(&self, pos: IVec2) -> impl Iterator<Item = (IVec2, &T)> {
+        self.neighbours(pos, Dir8::all().map(Dir8::delta))
+    }
+
+    /// Create a grid of the same size with each cell transformed by `f`
+    #[allow(unused)]
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Create a grid of the given size, taking each cell from the position `source` maps it to
+    fn remap(&self, width: i32, height: i32, source: impl Fn(IVec2) -> IVec2) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(width, height, |pos| self[source(pos)].clone())
+    }
+
+    /// Return the grid mirrored along its main diagonal, so that rows become columns
+    #[allow(unused)]
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |pos| IVec2::new(pos.y, pos.x))
+    }
+
+    /// Return the grid turned a quarter turn clockwise
+    #[allow(unused)]
+    pub fn rotate_cw(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        self.remap(self.height, self.width, |pos| {
+            IVec2::new(pos.y, height - 1 - pos.x)
+        })
+    }
+
+    /// Return the grid turned a quarter turn counterclockwise
+    #[allow(unused)]
+    pub fn rotate_ccw(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        self.remap(self.height, self.width, |pos| {
+            IVec2::new(width - 1 - pos.y, pos.x)
+        })
+    }
+
+    /// Return the grid mirrored left to right
+    #[allow(unused)]
+    pub fn flip_horizontal(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        self.remap(self.width, self.height, |pos| {
+            IVec2::new(width - 1 - pos.x, pos.y)
+        })
+    }
+
+    /// Return the grid mirrored top to bottom
+    #[allow(unused)]
+    pub fn flip_vertical(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        self.remap(self.width, self.height, |pos| {
+            IVec2::new(pos.x, height - 1 - pos.y)
+        })
+    }
+
+    /// Return a value that displays the grid a row per line, drawing each cell with `f`
+    #[allow(unused)]
+    pub fn display<F: Fn(&T) -> char>(&self, f: F) -> GridDisplay<'_, T, F> {
+        GridDisplay { grid: self, f }
+    }
+}
+
+impl<T> Index<IVec2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: IVec2) -> &T {
+        let Some(cell) = self.get(pos) else {
+            panic!(
+                "position ({}, {}) is outside a {}x{} grid",
+                pos.x, pos.y, self.width, self.height
+            );
+        };
+        cell
+    }
+}
+
+impl<T> IndexMut<IVec2> for Grid<T> {
+    fn index_mut(&mut self, pos: IVec2) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        let Some(cell) = self.get_mut(pos) else {
+            panic!(
+                "position ({}, {}) is outside a {}x{} grid",
+                pos.x, pos.y, width, height
+            );
+        };
+        cell
+    }
+}
+
+/// Displays a grid through a mapping from cells to characters, as returned by `Grid::display`
+pub struct GridDisplay<'g, T, F> {
+    grid: &'g Grid<T>,
+    f: F,
+}
+
+impl<T, F: Fn(&T) -> char> fmt::Display for GridDisplay<'_, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.grid.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", (self.f)(cell))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a grid of characters from its rows
+    fn grid(rows: &[&str]) -> Grid<char> {
+        Grid::from_cells(rows[0].len() as i32, rows.concat().chars().collect())
+    }
+
+    fn text(grid: &Grid<char>) -> String {
+        grid.display(|&c| c).to_string()
+    }
+
+    #[test]
+    fn size_and_access() {
+        let grid = grid(&["abc", "def"]);
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.size(), IVec2::new(3, 2));
+        assert_eq!(grid.get(IVec2::new(2, 1)), Some(&'f'));
+        for outside in [
+            IVec2::new(3, 0),
+            IVec2::new(0, 2),
+            IVec2::new(-1, 0),
+            IVec2::new(0, -1),
+        ] {
+            assert!(!grid.contains(outside));
+            assert_eq!(grid.get(outside), None);
+        }
+        assert_eq!(grid[IVec2::new(1, 0)], 'b');
+    }
+
+    #[test]
+    #[should_panic(expected = "position (3, 0) is outside a 3x2 grid")]
+    fn index_outside() {
+        let _ = grid(&["abc", "def"])[IVec2::new(3, 0)];
+    }
+
+    #[test]
+    #[should_panic(expected = "5 cells do not make rows of 2")]
+    fn ragged_cells() {
+        Grid::from_cells(2, vec![0; 5]);
+    }
+
+    #[test]
+    fn changing_cells() {
+        let mut grid = grid(&["abc", "def"]);
+        grid[IVec2::new(0, 1)] = 'x';
+        *grid.get_mut(IVec2::new(2, 0)).unwrap() = 'y';
+        assert!(grid.get_mut(IVec2::new(0, 2)).is_none());
+        for (pos, cell) in grid.iter_mut() {
+            if pos.x == 1 {
+                *cell = cell.to_ascii_uppercase();
+            }
+        }
+        assert_eq!(text(&grid), "aBy\nxEf");
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = grid(&["abc", "def"]);
+        assert_eq!(grid.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(grid.row(2), None);
+        assert_eq!(grid.row(-1), None);
+        assert_eq!(grid.rows().count(), 2);
+
+        assert_eq!(grid.column(2).collect::<String>(), "cf");
+        assert_eq!(grid.column(3).count(), 0);
+        assert_eq!(grid.column(-1).count(), 0);
+        let columns = grid
+            .columns()
+            .map(|column| column.collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(columns, ["ad", "be", "cf"]);
+    }
+
+    #[test]
+    fn empty_grid() {
+        let grid = Grid::new(0, 0, '.');
+        assert_eq!(grid.rows().count(), 0);
+        assert_eq!(grid.columns().count(), 0);
+        assert_eq!(grid.iter().count(), 0);
+        assert_eq!(text(&grid), "");
+    }
+
+    #[test]
+    fn searching() {
+        let grid = grid(&["a.b", "..b"]);
+        assert_eq!(grid.find(|&c| c == 'b'), Some(IVec2::new(2, 0)));
+        assert_eq!(grid.find(|&c| c == 'z'), None);
+        let positions = grid.positions(|&c| c != '.').collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            [IVec2::new(0, 0), IVec2::new(2, 0), IVec2::new(2, 1)]
+        );
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = grid(&["abc", "def"]);
+        let cells = |neighbours: Vec<(IVec2, &char)>| {
+            neighbours.into_iter().map(|(_, &c)| c).collect::<String>()
+        };
+        assert_eq!(cells(grid.neighbours4(IVec2::new(0, 0)).collect()), "bd");
+        assert_eq!(cells(grid.neighbours4(IVec2::new(1, 1)).collect()), "bfd");
+        assert_eq!(cells(grid.neighbours8(IVec2::new(1, 0)).collect()), "cfeda");
+        assert_eq!(cells(grid.neighbours8(IVec2::new(2, 1)).collect()), "ceb");
+        let (pos, _) = grid.neighbours4(IVec2::new(2, 1)).next().unwrap();
+        assert_eq!(pos, IVec2::new(2, 0));
+    }
+
+    #[test]
+    fn transforms_of_non_square_grid() {
+        let grid = grid(&["abc", "def"]);
+        assert_eq!(text(&grid.transpose()), "ad\nbe\ncf");
+        assert_eq!(text(&grid.rotate_cw()), "da\neb\nfc");
+        assert_eq!(text(&grid.rotate_ccw()), "cf\nbe\nad");
+        assert_eq!(text(&grid.flip_horizontal()), "cba\nfed");
+        assert_eq!(text(&grid.flip_vertical()), "def\nabc");
+        assert_eq!(grid.rotate_cw().size(), IVec2::new(2, 3));
+    }
+
+    #[test]
+    fn transforms_compose() {
+        let grid = grid(&["abcd", "efgh", "ijkl"]);
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+        assert_eq!(
+            grid.rotate_cw().rotate_cw(),
+            grid.flip_horizontal().flip_vertical()
+        );
+        assert_eq!(grid.rotate_cw().rotate_cw().rotate_cw().rotate_cw(), grid);
+        assert_eq!(grid.transpose().transpose(), grid);
+        assert_eq!(grid.rotate_cw(), grid.transpose().flip_horizontal());
+    }
+
+    #[test]
+    fn mapping() {
+        let grid = grid(&["a#", "#b"]).map(|&c| c == '#');
+        assert_eq!(grid.cells(), [false, true, true, false]);
+        assert_eq!(
+            grid.display(|&wall| if wall { '#' } else { '.' })
+                .to_string(),
+            ".#\n#."
+        );
+    }
+}
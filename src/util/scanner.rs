@@ -1,20 +1,316 @@
 #![allow(unused)]
 
-use std::{collections::HashMap, str::FromStr, sync::Mutex};
+pub mod combinators;
+pub mod stream;
+pub mod trace;
 
-use once_cell::sync::Lazy;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+};
+
+pub use aoc2023_macros::Scan;
+use num::{BigInt, BigUint};
 use regex::{Captures, Regex, RegexBuilder};
 
-static REGEX_CACHE: Lazy<Mutex<HashMap<&'static str, Regex>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
+use super::grid::Grid;
+use combinators::ParseIter;
+use trace::ScanEvent;
+
+thread_local! {
+    /// Regular expressions compiled from string patterns, kept per thread so that no lock is taken
+    static REGEX_CACHE: RefCell<HashMap<Arc<str>, Result<Regex, regex::Error>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Call `f` with the cached regular expression for a pattern, compiling it on first use
+///
+/// `key` is only called to make the cache key when the pattern is not cached yet. A pattern that
+/// fails to compile is cached too, so the error is returned again without recompiling it.
+fn with_cached_regex<T>(
+    pattern: &str,
+    key: impl FnOnce() -> Arc<str>,
+    f: impl FnOnce(&Regex) -> T,
+) -> Result<T, regex::Error> {
+    REGEX_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if !cache.contains_key(pattern) {
+            cache.insert(key(), compile_anchored(pattern));
+        }
+        cache[pattern].as_ref().map(f).map_err(Clone::clone)
+    })
+}
+
+/// Compile a pattern so that it only matches at the start of the input
+fn compile_anchored(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(&format!("^(?:{})", pattern)).build()
+}
+
+/// A regular expression that is compiled the first time it is used and then shared by all threads
+///
+/// Patterns are normally created with `pattern!`, which keeps each one in a static of its own.
+#[derive(Debug)]
+pub struct Pattern {
+    source: &'static str,
+    regex: OnceLock<Result<Regex, regex::Error>>,
+}
+
+impl Pattern {
+    /// Create a pattern from a regular expression, without compiling it yet
+    pub const fn new(source: &'static str) -> Pattern {
+        Pattern {
+            source,
+            regex: OnceLock::new(),
+        }
+    }
+
+    /// Return the regular expression the pattern was created from
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    /// Return the compiled regular expression, anchored to the start of the input, or the error
+    /// from compiling it
+    pub fn regex(&self) -> Result<&Regex, regex::Error> {
+        self.regex
+            .get_or_init(|| compile_anchored(self.source))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+}
+
+/// Return a `&'static Pattern` for a regular expression, compiled at most once per program
+macro_rules! pattern {
+    ($source:literal) => {{
+        static PATTERN: $crate::util::scanner::Pattern =
+            $crate::util::scanner::Pattern::new($source);
+        &PATTERN
+    }};
+}
+pub(crate) use pattern;
+
+/// A regular expression that a scanner can match
+///
+/// This is either a string, which is compiled once per thread and cached under an `Arc<str>` of
+/// its text, a `Pattern`, which is compiled once per program, or a precompiled `Regex`. Strings
+/// can be built at runtime, and are matched only at the current position. A `Regex` is used as
+/// it is, and only counts as a match if it matches at the current position, so it should start
+/// with `^` to avoid searching the rest of the input.
+///
+/// A regular expression that fails to compile never matches, and the scanner methods that return
+/// an error report why it failed.
+pub trait RegexSource {
+    /// Return the regular expression as written
+    fn source(&self) -> &str;
+
+    /// Call `f` with the compiled regular expression, or return the error from compiling it
+    fn with_regex<T>(&self, f: impl FnOnce(&Regex) -> T) -> Result<T, regex::Error>;
+}
+
+impl<R: RegexSource + ?Sized> RegexSource for &R {
+    fn source(&self) -> &str {
+        (**self).source()
+    }
+
+    fn with_regex<T>(&self, f: impl FnOnce(&Regex) -> T) -> Result<T, regex::Error> {
+        (**self).with_regex(f)
+    }
+}
+
+impl RegexSource for str {
+    fn source(&self) -> &str {
+        self
+    }
+
+    fn with_regex<T>(&self, f: impl FnOnce(&Regex) -> T) -> Result<T, regex::Error> {
+        with_cached_regex(self, || Arc::from(self), f)
+    }
+}
+
+impl RegexSource for String {
+    fn source(&self) -> &str {
+        self
+    }
+
+    fn with_regex<T>(&self, f: impl FnOnce(&Regex) -> T) -> Result<T, regex::Error> {
+        self.as_str().with_regex(f)
+    }
+}
+
+impl RegexSource for Arc<str> {
+    fn source(&self) -> &str {
+        self
+    }
+
+    fn with_regex<T>(&self, f: impl FnOnce(&Regex) -> T) -> Result<T, regex::Error> {
+        with_cached_regex(self, || self.clone(), f)
+    }
+}
+
+impl RegexSource for Pattern {
+    fn source(&self) -> &str {
+        self.source
+    }
+
+    fn with_regex<T>(&self, f: impl FnOnce(&Regex) -> T) -> Result<T, regex::Error> {
+        self.regex().map(f)
+    }
+}
+
+impl RegexSource for Regex {
+    fn source(&self) -> &str {
+        self.as_str()
+    }
+
+    fn with_regex<T>(&self, f: impl FnOnce(&Regex) -> T) -> Result<T, regex::Error> {
+        Ok(f(self))
+    }
+}
+
+/// An error that occurred while scanning, recording what was expected and where
+///
+/// Along with the byte offset, the error keeps the line and column of the failure and the text of
+/// that line, so that it can be rendered without the input at hand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScanError {
+    expected: String,
+    offset: usize,
+    line: usize,
+    column: usize,
+    source_line: String,
+}
+
+impl ScanError {
+    /// Create a new scan error for something that was expected at an offset in the input
+    pub fn new(expected: impl Into<String>, input: &str, offset: usize) -> ScanError {
+        let mut offset = offset.min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+
+        ScanError {
+            expected: expected.into(),
+            offset,
+            line: input[..offset].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            source_line: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+        }
+    }
+
+    /// Return a description of what was expected
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// Return the offset relative to the start of the input at which the error occurred
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Return the 1-based line of the input at which the error occurred
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Return the 1-based column, in characters, at which the error occurred
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Render the line of input the error occurred on, with a caret under the failure point
+    ///
+    /// The output follows the style of rustc's diagnostics:
+    ///
+    /// ```text
+    ///   --> line 3, column 7
+    ///    |
+    ///  3 | 32T3K 7a5
+    ///    |       ^ expected unsigned integer
+    /// ```
+    pub fn render(&self) -> String {
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        // Keep tabs in the padding so that the caret lines up with the text above it
+        let padding = self
+            .source_line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+
+        format!(
+            "{gutter}--> line {}, column {}\n{gutter} |\n{number} | {}\n{gutter} | {padding}^ expected {}",
+            self.line, self.column, self.source_line, self.expected
+        )
+    }
+}
+
+/// Where a scanner's string sits in the whole input, for a scanner over part of a stream
+///
+/// The string always starts at the beginning of a line, so only offsets and line numbers need
+/// shifting; columns are unaffected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Origin {
+    /// The offset of the start of the string in the whole input
+    offset: usize,
+    /// The number of lines of the whole input before the string
+    line: usize,
+}
+
+impl ScanError {
+    /// Move an error made from part of the input to its place in the whole input
+    fn shifted(mut self, origin: Origin) -> ScanError {
+        self.offset += origin.offset;
+        self.line += origin.line;
+        self
+    }
+}
+
+impl std::fmt::Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected {} at line {}, column {}",
+            self.expected, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for ScanError {}
 
 /// Helper for scanning strings for tokens
 #[derive(Clone, Debug)]
 pub struct Scanner<'s> {
     string: &'s str,
     offset: usize,
+    end: usize,
+    origin: Origin,
     ignore_whitespace: bool,
-    debug: bool,
+    quiet: bool,
+    /// The furthest offset that a scan has looked at the input from, so that a stream can tell
+    /// whether a failed scan ran out of input
+    furthest: Cell<usize>,
+}
+
+/// A position in a scanner's input, saved with `Scanner::checkpoint`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Checkpoint {
+    offset: usize,
+}
+
+impl Checkpoint {
+    /// Return the saved offset relative to the start of the input
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
 }
 
 /// A token that was scanned from a string
@@ -25,6 +321,7 @@ pub struct Token<'s> {
     string: &'s str,
     start: usize,
     end: usize,
+    origin: Origin,
 }
 
 impl std::fmt::Display for Token<'_> {
@@ -36,7 +333,12 @@ impl std::fmt::Display for Token<'_> {
 impl<'s> Token<'s> {
     /// Create a new token from an input string slice and a range
     pub fn new(string: &'s str, start: usize, end: usize) -> Token<'s> {
-        Token { string, start, end }
+        Token {
+            string,
+            start,
+            end,
+            origin: Origin::default(),
+        }
     }
 
     /// Return the token's value as a string slice
@@ -49,15 +351,46 @@ impl<'s> Token<'s> {
         self.string[self.start..self.end].parse::<T>().ok()
     }
 
-    /// Parse the token's value and return the result, or panic if parsing fails
-    pub fn parse<T: FromStr>(&self) -> T {
-        self.try_parse()
-            .expect(format!("Failed to parse token '{}'", self.as_str()).as_str())
+    /// Parse the token's value and return the result, or an error if parsing fails
+    pub fn parse<T: FromStr>(&self) -> Result<T, ScanError> {
+        self.try_parse().ok_or_else(|| {
+            self.error(format!(
+                "a valid {} but found '{}'",
+                short_type_name::<T>(),
+                self.as_str()
+            ))
+        })
+    }
+
+    /// Create an error for something that was expected where the token starts
+    pub fn error(&self, expected: impl Into<String>) -> ScanError {
+        ScanError::new(expected, self.string, self.start).shifted(self.origin)
+    }
+
+    /// Create a scanner over just the token's text
+    ///
+    /// Offsets, tokens and errors from the new scanner are relative to the whole input, so errors
+    /// still point at the right line.
+    pub fn scanner(&self, ignore_whitespace: bool) -> Scanner<'s> {
+        Scanner {
+            string: self.string,
+            offset: self.start,
+            end: self.end,
+            origin: self.origin,
+            ignore_whitespace,
+            quiet: false,
+            furthest: Cell::new(0),
+        }
     }
 
     /// Return the token's offset relative to the start of the input
     pub fn offset(&self) -> usize {
-        self.start
+        self.origin.offset + self.start
+    }
+
+    /// Return the token's range of bytes in the string it was scanned from
+    fn span(&self) -> (usize, usize) {
+        (self.start, self.end)
     }
 
     /// Return the length of the token
@@ -66,44 +399,353 @@ impl<'s> Token<'s> {
     }
 }
 
+/// A block of lines read by `Scanner::scan_section`, ended by a blank line or the end of input
+#[derive(Clone, Debug)]
+pub struct Section<'s> {
+    whole: Token<'s>,
+    header: Option<Token<'s>>,
+    body: Token<'s>,
+}
+
+impl<'s> Section<'s> {
+    /// Return a token spanning the whole section
+    pub fn whole(&self) -> &Token<'s> {
+        &self.whole
+    }
+
+    /// Return the first line of the section if it ends with a colon, such as `seed-to-soil map:`
+    pub fn header(&self) -> Option<&Token<'s>> {
+        self.header.as_ref()
+    }
+
+    /// Return a token spanning the section after its header, or the whole section if it has none
+    pub fn body(&self) -> &Token<'s> {
+        &self.body
+    }
+
+    /// Return the section's offset relative to the start of the input
+    pub fn offset(&self) -> usize {
+        self.whole.offset()
+    }
+
+    /// Create a scanner over the section's body, with offsets relative to the whole input
+    pub fn scanner(&self, ignore_whitespace: bool) -> Scanner<'s> {
+        self.body.scanner(ignore_whitespace)
+    }
+
+    /// Create an error for something that was expected where the section starts
+    pub fn error(&self, expected: impl Into<String>) -> ScanError {
+        self.whole.error(expected)
+    }
+}
+
+/// The capture groups of a regular expression match, as tokens that span the input
+#[derive(Clone, Debug)]
+pub struct ScanCaptures<'s> {
+    whole: Token<'s>,
+    groups: Vec<Option<Token<'s>>>,
+    names: Vec<(String, usize)>,
+}
+
+impl<'s> ScanCaptures<'s> {
+    /// Return the token for the whole match
+    pub fn whole(&self) -> &Token<'s> {
+        &self.whole
+    }
+
+    /// Return the token for a group by its 1-based index, or None if the group did not match
+    pub fn get(&self, index: usize) -> Option<&Token<'s>> {
+        self.groups.get(index.checked_sub(1)?)?.as_ref()
+    }
+
+    /// Return the token for a named group, or None if the group did not match
+    pub fn name(&self, name: &str) -> Option<&Token<'s>> {
+        let &(_, index) = self.names.iter().find(|(group, _)| group == name)?;
+        self.get(index)
+    }
+
+    /// Return the token for a named group, or an error at the start of the match if the group did
+    /// not match
+    pub fn require(&self, name: &str) -> Result<&Token<'s>, ScanError> {
+        self.name(name)
+            .ok_or_else(|| self.whole.error(format!("a value for '{}'", name)))
+    }
+
+    /// Parse the value of a named group, failing if the group did not match or cannot be parsed
+    pub fn parse<T: FromStr>(&self, name: &str) -> Result<T, ScanError> {
+        self.require(name)?.parse()
+    }
+}
+
+/// A type that can be built from the named groups of a regular expression match
+///
+/// Implementations are used with `Scanner::scan_regex_as`, typically for one line of input.
+pub trait FromCaptures<'s>: Sized {
+    fn from_captures(captures: &ScanCaptures<'s>) -> Result<Self, ScanError>;
+}
+
+/// The iterator returned by `Scan::scan_all`
+pub type ScanAll<'s, T> = ParseIter<'s, T, fn(&mut Scanner<'s>) -> Result<T, ScanError>>;
+
+/// Return the name of a type without its module path
+fn short_type_name<T>() -> &'static str {
+    let type_name = std::any::type_name::<T>();
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
+/// An integer type that `Scanner::scan_int` and its variants can produce
+pub trait ScanInt: Sized {
+    /// Whether the type can hold negative values, in which case a leading `-` is scanned
+    const SIGNED: bool;
+
+    /// Convert digits in a radix, with an optional leading `-`, or return None if the value does
+    /// not fit in the type
+    fn from_digits(digits: &str, radix: u32) -> Option<Self>;
+}
+
+macro_rules! impl_scan_int {
+    ($signed:literal: $($ty:ty),+) => {
+        $(
+            impl ScanInt for $ty {
+                const SIGNED: bool = $signed;
+
+                fn from_digits(digits: &str, radix: u32) -> Option<$ty> {
+                    <$ty>::from_str_radix(digits, radix).ok()
+                }
+            }
+
+            impl<'s> Scan<'s> for $ty {
+                fn scan(scanner: &mut Scanner<'s>) -> Result<$ty, ScanError> {
+                    scanner.scan_int()
+                }
+            }
+        )+
+    };
+}
+
+impl_scan_int!(true: i8, i16, i32, i64, i128, isize);
+impl_scan_int!(false: u8, u16, u32, u64, u128, usize);
+
+impl ScanInt for BigInt {
+    const SIGNED: bool = true;
+
+    fn from_digits(digits: &str, radix: u32) -> Option<BigInt> {
+        BigInt::parse_bytes(digits.as_bytes(), radix)
+    }
+}
+
+impl<'s> Scan<'s> for BigInt {
+    fn scan(scanner: &mut Scanner<'s>) -> Result<BigInt, ScanError> {
+        scanner.scan_int()
+    }
+}
+
+impl ScanInt for BigUint {
+    const SIGNED: bool = false;
+
+    fn from_digits(digits: &str, radix: u32) -> Option<BigUint> {
+        BigUint::parse_bytes(digits.as_bytes(), radix)
+    }
+}
+
+impl<'s> Scan<'s> for BigUint {
+    fn scan(scanner: &mut Scanner<'s>) -> Result<BigUint, ScanError> {
+        scanner.scan_int()
+    }
+}
+
+/// Return the number of ASCII digits at the start of a string
+fn count_digits(s: &str) -> usize {
+    s.bytes().take_while(u8::is_ascii_digit).count()
+}
+
+/// A type that can be scanned from the start of the remaining input
+///
+/// Structs can derive this with `#[derive(Scan)]` and a `#[scan("...")]` format, in which
+/// `{field}` scans a field with its own `Scan` implementation and `{field: sep(',')}` scans a
+/// `Vec` field as a list. A list separated by whitespace runs to the end of the line or to the
+/// literal text that follows it in the format; any other list has at least one item. Whitespace in
+/// the format matches one or more spaces and tabs, so `"a b"` does not accept `ab`, and apart from
+/// the spaces around list items, spaces are only skipped before a field that starts the format.
+/// Enums give a format for each variant, and scan as the first variant that matches.
+pub trait Scan<'s>: Sized {
+    /// Scan a value, advancing the scanner past it
+    fn scan(scanner: &mut Scanner<'s>) -> Result<Self, ScanError>;
+
+    /// Scan values one after another until the end of the input, stopping after the first error
+    fn scan_all(input: &'s str) -> ScanAll<'s, Self> {
+        ParseIter::new(Scanner::new(input, true), Self::scan)
+    }
+}
+
+macro_rules! impl_scan {
+    ($method:ident, $expected:literal: $($ty:ty),+) => {
+        $(
+            impl<'s> Scan<'s> for $ty {
+                fn scan(scanner: &mut Scanner<'s>) -> Result<$ty, ScanError> {
+                    scanner
+                        .$method()
+                        .ok_or_else(|| scanner.error($expected))?
+                        .parse()
+                }
+            }
+        )+
+    };
+}
+
+impl_scan!(try_scan_float, "float": f32, f64);
+impl_scan!(try_scan_word, "word": String);
+
+impl<'s> Scan<'s> for char {
+    fn scan(scanner: &mut Scanner<'s>) -> Result<char, ScanError> {
+        scanner
+            .try_scan_char_if(|c| !c.is_whitespace())
+            .ok_or_else(|| scanner.error("character"))?
+            .parse()
+    }
+}
+
 impl<'s> Scanner<'s> {
     /// Create a new scanner from an input string
     ///
     /// If `ignore_whitespace` is true, the scanner will skip over whitespace before scanning for tokens.
     /// Tokens themselves may still contain whitespace.
-    pub fn new(string: &str, ignore_whitespace: bool) -> Scanner {
+    pub fn new(string: &str, ignore_whitespace: bool) -> Scanner<'_> {
         Scanner {
             string,
             ignore_whitespace,
             offset: 0,
-            debug: false,
+            end: string.len(),
+            origin: Origin::default(),
+            quiet: false,
+            furthest: Cell::new(0),
         }
     }
 
-    /// Enable debug mode, which will print debug information to stdout
-    pub fn enable_debug(mut self) -> Scanner<'s> {
-        self.debug = true;
-        self
+    /// Create a scanner over a string that starts at `origin` in the whole input
+    fn with_origin(
+        string: &str,
+        offset: usize,
+        ignore_whitespace: bool,
+        origin: Origin,
+    ) -> Scanner<'_> {
+        Scanner {
+            string,
+            ignore_whitespace,
+            offset,
+            end: string.len(),
+            origin,
+            quiet: false,
+            furthest: Cell::new(0),
+        }
+    }
+
+    /// Create a token for a range of the string
+    fn token(&self, start: usize, end: usize) -> Token<'s> {
+        Token {
+            string: self.string,
+            start,
+            end,
+            origin: self.origin,
+        }
+    }
+
+    /// Return the input from an offset up to the end of the part this scanner covers
+    fn rest_from(&self, offset: usize) -> &'s str {
+        self.reach(offset);
+        &self.string[offset..self.end]
+    }
+
+    /// Note that a scan has looked at the input from an offset onward
+    fn reach(&self, offset: usize) {
+        self.furthest.set(self.furthest.get().max(offset));
+    }
+
+    /// Return true if a scan has looked for input at the end of the part this scanner covers
+    fn reached_end(&self) -> bool {
+        self.furthest.get() >= self.end
     }
 
-    fn maybe_skip_whitespace(&mut self) {
+    /// Return the offset where the next token starts, past any whitespace the scanner ignores
+    fn token_start(&self) -> usize {
         if self.ignore_whitespace {
-            self.skip_whitespace();
+            let start = self.end - self.remaining().trim_start().len();
+            self.reach(start);
+            start
+        } else {
+            self.offset
+        }
+    }
+
+    /// Move past a token that was matched at the current position
+    fn consume(&mut self, token: Token<'s>) -> Token<'s> {
+        self.offset = token.end;
+        token
+    }
+
+    /// Report an attempt to scan for `pattern` to the trace sink, if one is installed
+    ///
+    /// The pattern is only described when there is a sink to send it to. Scanners made by `peek`
+    /// report nothing, since they never consume anything.
+    fn trace(&self, pattern: impl FnOnce() -> String, token: Option<&Token<'s>>) {
+        if self.quiet || !trace::is_active() {
+            return;
         }
+        let (start, end) = token.map_or_else(
+            || (self.token_start(), self.token_start()),
+            |token| token.span(),
+        );
+        trace::emit(&ScanEvent {
+            pattern: &pattern(),
+            start: self.origin.offset + start,
+            end: self.origin.offset + end,
+            skipped: start.saturating_sub(self.offset),
+            text: &self.string[start..end],
+            matched: token.is_some(),
+        });
     }
 
-    fn consume_token(&mut self, len: usize) -> Token<'s> {
-        let offset = self.offset;
-        self.offset += len;
-        let result = Token::new(self.string, offset, offset + len);
+    /// Trace an attempt to scan for `pattern`, and move past the token if one was found
+    fn accept(
+        &mut self,
+        pattern: impl FnOnce() -> String,
+        token: Option<Token<'s>>,
+    ) -> Option<Token<'s>> {
+        self.trace(pattern, token.as_ref());
+        token.map(|token| self.consume(token))
+    }
 
-        if self.debug {
-            println!("Consumed token: {:?}", result.as_str());
+    /// Save the current position so that it can be returned to with `restore`
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            offset: self.offset,
         }
+    }
+
+    /// Return to a position saved with `checkpoint`
+    pub fn restore(&mut self, checkpoint: Checkpoint) {
+        self.offset = checkpoint.offset;
+    }
 
+    /// Run `f` and keep what it consumed only if it returns a value
+    ///
+    /// If `f` returns None, the scanner is restored to where it was before the call.
+    pub fn transaction<T>(&mut self, f: impl FnOnce(&mut Scanner<'s>) -> Option<T>) -> Option<T> {
+        let checkpoint = self.checkpoint();
+        let result = f(self);
+        if result.is_none() {
+            self.restore(checkpoint);
+        }
         result
     }
 
+    /// Run `f` on a copy of the scanner and return its result without consuming anything
+    pub fn peek<T>(&self, f: impl FnOnce(&mut Scanner<'s>) -> Option<T>) -> Option<T> {
+        let mut scanner = self.clone();
+        scanner.quiet = true;
+        f(&mut scanner)
+    }
+
     /// Seek to a particular offset in the input
     pub fn seek(&mut self, offset: usize) {
         self.offset = offset;
@@ -111,7 +753,7 @@ impl<'s> Scanner<'s> {
 
     /// Return the slice of the string that remains to be consumed
     pub fn remaining(&self) -> &'s str {
-        &self.string[self.offset..]
+        self.rest_from(self.offset)
     }
 
     /// Return true if there is no more input to consume
@@ -128,132 +770,526 @@ impl<'s> Scanner<'s> {
         self.offset
     }
 
+    /// Create an error for something that was expected at the current offset
+    pub fn error(&self, expected: impl Into<String>) -> ScanError {
+        ScanError::new(expected, self.string, self.token_start()).shifted(self.origin)
+    }
+
     /// Scan for a constant string and return the matched string slice, or None if no match is found
     pub fn try_scan_string(&mut self, s: &str) -> Option<Token<'s>> {
-        self.maybe_skip_whitespace();
+        let token = self.peek_string(s);
+        self.accept(|| format!("string {:?}", s), token)
+    }
 
-        if self.remaining().starts_with(&s) {
-            Some(self.consume_token(s.len()))
-        } else {
-            None
-        }
+    /// Match a constant string without consuming it, or return None if it does not come next
+    pub fn peek_string(&self, s: &str) -> Option<Token<'s>> {
+        let start = self.token_start();
+        self.rest_from(start)
+            .starts_with(s)
+            .then(|| self.token(start, start + s.len()))
     }
 
-    /// Scan for a constant string and return the matched string slice, or panic if no match is found
-    pub fn scan_string(&mut self, s: &str) -> Token<'s> {
-        let offset = self.offset;
+    /// Scan for a constant string and return the matched string slice, or an error if no match is found
+    pub fn scan_string(&mut self, s: &str) -> Result<Token<'s>, ScanError> {
         self.try_scan_string(s)
-            .expect(format!("Expected string '{}' at {}", s, offset).as_str())
+            .ok_or_else(|| self.error(format!("'{}'", s)))
+    }
+
+    /// Scan for a regular expression match and return its capture groups, or None if no match is found
+    ///
+    /// Every capture token spans its group's text in the original input, so its offset can be used
+    /// in errors.
+    pub fn try_scan_regex_named(&mut self, regex: impl RegexSource) -> Option<ScanCaptures<'s>> {
+        let base = self.token_start();
+        let found = regex.with_regex(|regex| {
+            if self.is_finished() {
+                return None;
+            }
+            let captures = regex
+                .captures(self.rest_from(base))
+                .filter(|captures| captures.get(0).is_some_and(|m| m.start() == 0))?;
+
+            let span = |m: regex::Match| self.token(base + m.start(), base + m.end());
+            let groups = captures
+                .iter()
+                .skip(1)
+                .map(|m| m.map(span))
+                .collect::<Vec<_>>();
+            let names = regex
+                .capture_names()
+                .enumerate()
+                .filter_map(|(index, name)| name.map(|name| (name.to_string(), index)))
+                .collect::<Vec<_>>();
+            Some((captures.get(0).map_or(0, |m| m.len()), groups, names))
+        });
+        let found = found.ok().flatten();
+        let whole = found
+            .as_ref()
+            .map(|&(len, _, _)| self.token(base, base + len));
+        let whole = self.accept(|| format!("regex '{}'", regex.source()), whole)?;
+        let (_, groups, names) = found?;
+
+        Some(ScanCaptures {
+            whole,
+            groups,
+            names,
+        })
+    }
+
+    /// Scan for a regular expression match and return its capture groups, or an error if no match is found
+    pub fn scan_regex_named(
+        &mut self,
+        regex: impl RegexSource,
+    ) -> Result<ScanCaptures<'s>, ScanError> {
+        self.try_scan_regex_named(&regex)
+            .ok_or_else(|| self.regex_error(&regex))
     }
 
     /// Scan for a regular expression match and return each matched capture group as its own token, or None if no match is found
+    ///
+    /// The regex must have exactly `N` groups, all of which take part in every match. Use
+    /// `try_scan_regex_optional_captures` for groups that may not.
     pub fn try_scan_regex_captures<const N: usize>(
         &mut self,
-        regex: &'static str,
+        regex: impl RegexSource,
     ) -> Option<[Token<'s>; N]> {
-        self.maybe_skip_whitespace();
+        let captures = self.try_scan_regex_optional_captures::<N>(&regex)?;
+        Some(captures.map(|token| {
+            token.unwrap_or_else(|| panic!("a group in regex '{}' did not match", regex.source()))
+        }))
+    }
 
-        if self.is_finished() {
-            return None;
-        }
+    /// Scan for a regular expression match and return each capture group as its own token, or None if no match is found
+    ///
+    /// The regex must have exactly `N` groups. Groups that do not take part in the match are None.
+    pub fn try_scan_regex_optional_captures<const N: usize>(
+        &mut self,
+        regex: impl RegexSource,
+    ) -> Option<[Option<Token<'s>>; N]> {
+        let captures = self.try_scan_regex_named(&regex)?;
+        assert_eq!(
+            captures.groups.len(),
+            N,
+            "regex '{}' has the wrong number of groups",
+            regex.source()
+        );
+        Some(std::array::from_fn(|i| captures.groups[i].clone()))
+    }
 
-        let mut cache = REGEX_CACHE.lock().unwrap();
-        let regex = cache.entry(regex).or_insert_with(|| {
-            RegexBuilder::new(format!("^{}", regex).as_str())
-                .build()
-                .unwrap()
-        });
+    /// Scan for a regular expression match and convert its named groups into a `T`, or None if no match is found
+    pub fn try_scan_regex_as<T: FromCaptures<'s>>(
+        &mut self,
+        regex: impl RegexSource,
+    ) -> Option<Result<T, ScanError>> {
+        self.try_scan_regex_named(&regex)
+            .map(|captures| T::from_captures(&captures))
+    }
 
-        if let Some(captures) = regex.captures(self.remaining()) {
-            if self.debug {
-                println!("Regex match ({}): {:?}", regex, captures);
-            }
-            let (all, extracted) = captures.extract::<N>();
-            self.consume_token(all.len());
-            Some(extracted.map(|token| Token::new(token, 0, token.len())))
-        } else {
-            None
-        }
+    /// Scan for a regular expression match and convert its named groups into a `T`, or return an error if no match is found
+    pub fn scan_regex_as<T: FromCaptures<'s>>(
+        &mut self,
+        regex: impl RegexSource,
+    ) -> Result<T, ScanError> {
+        T::from_captures(&self.scan_regex_named(&regex)?)
     }
 
     /// Scan for a regular expression match and return the matched string slice, or None if no match is found
-    pub fn try_scan_regex(&mut self, regex: &'static str) -> Option<Token<'s>> {
-        self.maybe_skip_whitespace();
+    pub fn try_scan_regex(&mut self, regex: impl RegexSource) -> Option<Token<'s>> {
+        let token = self.peek_regex(&regex);
+        self.accept(|| format!("regex '{}'", regex.source()), token)
+    }
 
+    /// Match a regular expression without consuming it, or return None if it does not match next
+    pub fn peek_regex(&self, regex: impl RegexSource) -> Option<Token<'s>> {
         if self.is_finished() {
             return None;
         }
 
-        let mut cache = REGEX_CACHE.lock().unwrap();
-        let regex = cache.entry(regex).or_insert_with(|| {
-            RegexBuilder::new(format!("^{}", regex).as_str())
-                .build()
-                .unwrap()
+        let start = self.token_start();
+        let len = regex.with_regex(|regex| {
+            regex
+                .find(self.rest_from(start))
+                .filter(|m| m.start() == 0)
+                .map(|m| m.len())
         });
+        let len = len.ok().flatten()?;
+        Some(self.token(start, start + len))
+    }
 
-        if let Some(m) = regex.find(self.remaining()) {
-            if self.debug {
-                println!("Regex match ({}): {:?}", regex, m);
-            }
-            Some(self.consume_token(m.len()))
-        } else {
-            None
-        }
+    /// Scan for a regular expression match and return the matched string slice, or an error if no match is found
+    pub fn scan_regex(&mut self, regex: impl RegexSource) -> Result<Token<'s>, ScanError> {
+        self.try_scan_regex(&regex)
+            .ok_or_else(|| self.regex_error(&regex))
     }
 
-    /// Scan for a regular expression match and return the matched string slice, or panic if no match is found
-    pub fn scan_regex(&mut self, regex: &'static str) -> Token<'s> {
-        let offset = self.offset;
-        self.try_scan_regex(regex)
-            .expect(format!("Expected regex '{}' at {}", regex, offset).as_str())
+    /// Create an error for a regular expression that did not match, or that failed to compile
+    fn regex_error(&self, regex: impl RegexSource) -> ScanError {
+        match regex.with_regex(|_| ()) {
+            Ok(()) => self.error(format!("match for regex '{}'", regex.source())),
+            Err(err) => {
+                // Syntax errors span several lines, ending with the one that says what is wrong
+                let err = err.to_string();
+                let reason = err.lines().last().unwrap_or_default();
+                self.error(format!(
+                    "regex '{}' to be valid ({})",
+                    regex.source(),
+                    reason
+                ))
+            }
+        }
     }
 
     /// Scan for a linebreak and return the matched string slice, or None if no match is found
     pub fn try_scan_linebreak(&mut self) -> Option<Token<'s>> {
-        self.try_scan_regex("(\r\n|\r|\n)")
+        let start = self.token_start();
+        let rest = self.rest_from(start);
+        let len = if rest.starts_with("\r\n") {
+            2
+        } else {
+            usize::from(rest.starts_with(['\r', '\n']))
+        };
+        let token = (len > 0).then(|| self.token(start, start + len));
+        self.accept(|| "linebreak".to_string(), token)
     }
 
-    /// Scan for a linebreak and return the matched string slice, or panic if no match is found
-    pub fn scan_linebreak(&mut self) -> Token<'s> {
-        let offset = self.offset;
+    /// Scan for a linebreak and return the matched string slice, or an error if no match is found
+    pub fn scan_linebreak(&mut self) -> Result<Token<'s>, ScanError> {
         self.try_scan_linebreak()
-            .expect(format!("Expected linebreak at {}", offset).as_str())
+            .ok_or_else(|| self.error("linebreak"))
     }
 
     /// Scan for a signed integer and return the matched string slice, or None if no match is found
     pub fn try_scan_signed_int(&mut self) -> Option<Token<'s>> {
-        self.try_scan_regex(r"-?\d+")
+        let start = self.token_start();
+        let rest = self.rest_from(start);
+        let sign = usize::from(rest.starts_with('-'));
+        let digits = count_digits(&rest[sign..]);
+        let token = (digits > 0).then(|| self.token(start, start + sign + digits));
+        self.accept(|| "signed integer".to_string(), token)
     }
 
-    /// Scan for a signed integer and return the matched string slice, or panic if no match is found
-    pub fn scan_signed_int(&mut self) -> Token<'s> {
-        let offset = self.offset;
+    /// Scan for a signed integer and return the matched string slice, or an error if no match is found
+    pub fn scan_signed_int(&mut self) -> Result<Token<'s>, ScanError> {
         self.try_scan_signed_int()
-            .expect(format!("Expected integer at {}", offset).as_str())
+            .ok_or_else(|| self.error("integer"))
     }
 
     /// Scan for an unsigned integer and return the matched string slice, or None if no match is found
     pub fn try_scan_unsigned_int(&mut self) -> Option<Token<'s>> {
-        self.try_scan_regex(r"\d+")
+        let start = self.token_start();
+        let digits = count_digits(self.rest_from(start));
+        let token = (digits > 0).then(|| self.token(start, start + digits));
+        self.accept(|| "unsigned integer".to_string(), token)
     }
 
-    /// Scan for an unsigned integer and return the matched string slice, or panic if no match is found
-    pub fn scan_unsigned_int(&mut self) -> Token<'s> {
-        let offset = self.offset;
+    /// Scan for an unsigned integer and return the matched string slice, or an error if no match is found
+    pub fn scan_unsigned_int(&mut self) -> Result<Token<'s>, ScanError> {
         self.try_scan_unsigned_int()
-            .expect(format!("Expected unsigned integer at {}", offset).as_str())
+            .ok_or_else(|| self.error("unsigned integer"))
+    }
+
+    /// Scan for an integer in a radix and convert it, or return None if there is no integer next
+    ///
+    /// A leading `-` is only scanned for signed types. In radix 2, 8 and 16, the digits may follow
+    /// a `0b`, `0o` or `0x` prefix. With a separator, the digits may be split into groups by runs
+    /// of it, as in `1_000_000` or `7  15   30`. A value that does not fit in `T` is an error at
+    /// the start of the integer, and leaves the scanner where it was.
+    ///
+    /// Panics if the radix is not in the range 2 to 36.
+    fn try_scan_int_with<T: ScanInt>(
+        &mut self,
+        radix: u32,
+        separator: Option<char>,
+    ) -> Option<Result<T, ScanError>> {
+        assert!(
+            (2..=36).contains(&radix),
+            "radix {} is not in the range 2 to 36",
+            radix
+        );
+        let start = self.token_start();
+        let rest = self.rest_from(start);
+        let mut digits = String::new();
+        let mut len = 0;
+
+        if T::SIGNED && rest.starts_with('-') {
+            digits.push('-');
+            len += 1;
+        }
+        let prefix = match radix {
+            2 => Some("0b"),
+            8 => Some("0o"),
+            16 => Some("0x"),
+            _ => None,
+        };
+        if let Some(prefix) = prefix.filter(|prefix| {
+            rest[len..].starts_with(prefix)
+                && rest[len + prefix.len()..].starts_with(|c: char| c.is_digit(radix))
+        }) {
+            len += prefix.len();
+        }
+
+        let digits_start = len;
+        let mut chars = rest[digits_start..].char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            if c.is_digit(radix) {
+                digits.push(c);
+                len = digits_start + index + c.len_utf8();
+                continue;
+            }
+            // A run of separators only belongs to the integer if another digit follows it
+            if Some(c) != separator || digits.trim_start_matches('-').is_empty() {
+                break;
+            }
+            while chars
+                .next_if(|&(_, next)| Some(next) == separator)
+                .is_some()
+            {}
+            if !chars.peek().is_some_and(|&(_, next)| next.is_digit(radix)) {
+                break;
+            }
+        }
+        let pattern = move || format!("{} in base {}", short_type_name::<T>(), radix);
+        if digits.trim_start_matches('-').is_empty() {
+            self.trace(pattern, None);
+            return None;
+        }
+
+        let token = self.token(start, start + len);
+        match T::from_digits(&digits, radix) {
+            Some(value) => {
+                self.accept(pattern, Some(token));
+                Some(Ok(value))
+            }
+            None => Some(Err(token.error(format!(
+                "an integer that fits in {} but found '{}'",
+                short_type_name::<T>(),
+                token.as_str()
+            )))),
+        }
+    }
+
+    /// Scan for a decimal integer and convert it, or return None if there is no integer next
+    pub fn try_scan_int<T: ScanInt>(&mut self) -> Option<Result<T, ScanError>> {
+        self.try_scan_int_with(10, None)
+    }
+
+    /// Scan for a decimal integer and convert it, or return an error if there is none or it does not fit in `T`
+    pub fn scan_int<T: ScanInt>(&mut self) -> Result<T, ScanError> {
+        self.try_scan_int()
+            .unwrap_or_else(|| Err(self.error("integer")))
+    }
+
+    /// Scan for an integer in a radix from 2 to 36 and convert it, or return None if there is no integer next
+    ///
+    /// Binary, octal and hexadecimal integers may start with a `0b`, `0o` or `0x` prefix. Panics if
+    /// the radix is out of range.
+    pub fn try_scan_int_radix<T: ScanInt>(&mut self, radix: u32) -> Option<Result<T, ScanError>> {
+        self.try_scan_int_with(radix, None)
+    }
+
+    /// Scan for an integer in a radix from 2 to 36 and convert it, or return an error if there is none or it does not fit in `T`
+    ///
+    /// Panics if the radix is out of range.
+    pub fn scan_int_radix<T: ScanInt>(&mut self, radix: u32) -> Result<T, ScanError> {
+        self.try_scan_int_radix(radix)
+            .unwrap_or_else(|| Err(self.error(format!("integer in base {}", radix))))
+    }
+
+    /// Scan for a decimal integer whose digits may be split into groups by runs of a separator,
+    /// such as `1_000_000`, or return None if there is no integer next
+    pub fn try_scan_int_grouped<T: ScanInt>(
+        &mut self,
+        separator: char,
+    ) -> Option<Result<T, ScanError>> {
+        self.try_scan_int_with(10, Some(separator))
+    }
+
+    /// Scan for a decimal integer whose digits may be split into groups by runs of a separator,
+    /// or return an error if there is none or it does not fit in `T`
+    pub fn scan_int_grouped<T: ScanInt>(&mut self, separator: char) -> Result<T, ScanError> {
+        self.try_scan_int_grouped(separator)
+            .unwrap_or_else(|| Err(self.error("integer")))
     }
 
     /// Scan for a floating-point number and return the matched string slice, or None if no match is found
     pub fn try_scan_float(&mut self) -> Option<Token<'s>> {
-        self.try_scan_regex(r"[-+]?[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)?")
+        self.try_scan_regex(pattern!(r"[-+]?[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)?"))
+    }
+
+    /// Scan for a floating-point number and return the matched string slice, or an error if no match is found
+    pub fn scan_float(&mut self) -> Result<Token<'s>, ScanError> {
+        self.try_scan_float().ok_or_else(|| self.error("float"))
+    }
+
+    /// Scan for a single character that satisfies a predicate, or return None if the next one does not
+    pub fn try_scan_char_if(&mut self, predicate: impl Fn(char) -> bool) -> Option<Token<'s>> {
+        self.scan_char_matching(|| "character".to_string(), predicate)
+    }
+
+    /// Scan for a single character from a set, such as `"?.#"`, or return None if the next one is not in it
+    pub fn try_scan_one_of(&mut self, chars: &str) -> Option<Token<'s>> {
+        self.scan_char_matching(|| format!("one of {:?}", chars), |c| chars.contains(c))
+    }
+
+    /// Scan for a single character that satisfies a predicate, tracing it as `pattern`
+    fn scan_char_matching(
+        &mut self,
+        pattern: impl FnOnce() -> String,
+        predicate: impl Fn(char) -> bool,
+    ) -> Option<Token<'s>> {
+        let start = self.token_start();
+        let token = self
+            .rest_from(start)
+            .chars()
+            .next()
+            .filter(|&c| predicate(c))
+            .map(|c| self.token(start, start + c.len_utf8()));
+        self.accept(pattern, token)
+    }
+
+    /// Scan for a run of one or more characters that satisfy a predicate, or return None if there is none
+    pub fn try_scan_while(&mut self, predicate: impl Fn(char) -> bool) -> Option<Token<'s>> {
+        self.scan_run_matching(|| "run of characters".to_string(), predicate)
+    }
+
+    /// Scan for a word of letters, digits and underscores, or return None if there is none
+    pub fn try_scan_word(&mut self) -> Option<Token<'s>> {
+        self.scan_run_matching(|| "word".to_string(), |c| c.is_alphanumeric() || c == '_')
+    }
+
+    /// Scan for a run of characters that satisfy a predicate, tracing it as `pattern`
+    fn scan_run_matching(
+        &mut self,
+        pattern: impl FnOnce() -> String,
+        predicate: impl Fn(char) -> bool,
+    ) -> Option<Token<'s>> {
+        let start = self.token_start();
+        let rest = self.rest_from(start);
+        let len = rest.find(|c: char| !predicate(c)).unwrap_or(rest.len());
+        let token = (len > 0).then(|| self.token(start, start + len));
+        self.accept(pattern, token)
+    }
+
+    /// Scan a rectangular block of lines into a grid, turning each character into a cell with `cell`
+    ///
+    /// The block runs to the end of the input or to the next blank line, which is left unconsumed
+    /// so that the blocks of a multi-block input can be read one after another. Every row must be
+    /// as wide as the first, and `cell` must accept every character; errors for a bad character
+    /// give its position in the grid as well as in the input.
+    pub fn scan_grid<T>(&mut self, cell: impl Fn(char) -> Option<T>) -> Result<Grid<T>, ScanError> {
+        let start = self.token_start();
+        let mut offset = start;
+        let mut width = None;
+        let mut cells = Vec::new();
+        let mut height = 0;
+
+        while offset < self.end {
+            let rest = self.rest_from(offset);
+            let (line, next) = match rest.find('\n') {
+                Some(end) => (&rest[..end], offset + end + 1),
+                None => (rest, self.end),
+            };
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line.is_empty() {
+                break;
+            }
+
+            let row_width = line.chars().count();
+            if *width.get_or_insert(row_width) != row_width {
+                return Err(self.token(offset, offset).error(format!(
+                    "{} cells like the first row, but row {} has {}",
+                    width.unwrap_or_default(),
+                    height,
+                    row_width
+                )));
+            }
+            for (x, (index, c)) in line.char_indices().enumerate() {
+                let value = cell(c).ok_or_else(|| {
+                    self.token(offset + index, offset + index).error(format!(
+                        "a grid cell at ({}, {}) but found '{}'",
+                        x, height, c
+                    ))
+                })?;
+                cells.push(value);
+            }
+
+            height += 1;
+            offset = next;
+        }
+
+        let Some(width) = width else {
+            self.trace(|| "grid".to_string(), None);
+            return Err(self.error("a grid"));
+        };
+        self.accept(|| "grid".to_string(), Some(self.token(start, offset)));
+        Ok(Grid::from_cells(width as i32, cells))
+    }
+
+    /// Scan the next section of lines, or return None if only blank lines remain
+    ///
+    /// Blank lines before the section are skipped. The section runs up to the next blank line or
+    /// the end of the input, and its first line is its header if it ends with a colon.
+    pub fn try_scan_section(&mut self) -> Option<Section<'s>> {
+        let mut start = self.offset;
+        let mut lines = Vec::new();
+        let mut offset = start;
+        while offset < self.end {
+            let rest = self.rest_from(offset);
+            let (line, next) = match rest.find('\n') {
+                Some(end) => (&rest[..end], offset + end + 1),
+                None => (rest, self.end),
+            };
+            let line = line.trim_end();
+            if !line.is_empty() {
+                lines.push((offset, offset + line.len()));
+            } else if lines.is_empty() {
+                start = next;
+            } else {
+                break;
+            }
+            offset = next;
+        }
+
+        let (Some(&(_, first_end)), Some(&(_, last_end))) = (lines.first(), lines.last()) else {
+            self.trace(|| "section".to_string(), None);
+            return None;
+        };
+        let whole = self.token(start, last_end);
+        let header = self.string[start..first_end]
+            .ends_with(':')
+            .then(|| self.token(start, first_end));
+        let body_start = match (&header, lines.get(1)) {
+            (None, _) => start,
+            (Some(_), Some(&(second_start, _))) => second_start,
+            (Some(_), None) => last_end,
+        };
+        let body = self.token(body_start, last_end);
+        self.accept(|| "section".to_string(), Some(whole.clone()));
+        Some(Section {
+            whole,
+            header,
+            body,
+        })
+    }
+
+    /// Scan the next section of lines, or return an error if only blank lines remain
+    pub fn scan_section(&mut self) -> Result<Section<'s>, ScanError> {
+        self.try_scan_section()
+            .ok_or_else(|| self.error("another section of input"))
+    }
+
+    /// Skip over leading spaces and tabs, but not linebreaks
+    pub fn skip_spaces(&mut self) {
+        self.offset += self
+            .remaining()
+            .find(|c: char| c != ' ' && c != '\t')
+            .unwrap_or(self.remaining().len());
     }
 
-    /// Scan for a floating-point number and return the matched string slice, or panic if no match is found
-    pub fn scan_float(&mut self) -> Token<'s> {
-        let offset = self.offset;
-        self.try_scan_float()
-            .expect(format!("Expected float at {}", offset).as_str())
+    /// Return true if only spaces and tabs remain before the next linebreak or the end of input
+    pub fn at_line_end(&self) -> bool {
+        let rest = self.remaining().trim_start_matches([' ', '\t']);
+        rest.is_empty() || rest.starts_with(['\r', '\n'])
     }
 
     /// Skip over leading whitespace
@@ -261,6 +1297,849 @@ impl<'s> Scanner<'s> {
         self.offset += self
             .remaining()
             .find(|c: char| !c.is_whitespace())
-            .unwrap_or(0);
+            .unwrap_or(self.remaining().len());
+    }
+}
+
+/// The scanning operations shared by `Scanner` and `stream::StreamScanner`
+///
+/// Code written against this trait can read from a string in memory or from a stream. Every
+/// operation runs a `Scanner` at the current position through one of the two required methods,
+/// which a stream may call more than once if the first attempt runs into the end of what it has
+/// read so far.
+pub trait TokenScanner {
+    /// Run `f` with a scanner at the current position and move past whatever it consumed
+    fn scan_with<T>(&mut self, f: impl FnMut(&mut Scanner<'_>) -> T) -> T;
+
+    /// Run `f` with a scanner at the current position and move past the token it returns
+    fn scan_token<E>(
+        &mut self,
+        f: impl for<'b> Fn(&mut Scanner<'b>) -> Result<Token<'b>, E>,
+    ) -> Result<Token<'_>, E>;
+
+    /// Return true if there is no more input to consume
+    fn is_finished(&mut self) -> bool {
+        self.scan_with(|scanner| Scanner::is_finished(scanner))
+    }
+
+    /// Create an error for something that was expected at the current offset
+    fn error(&mut self, expected: impl Into<String>) -> ScanError {
+        let expected = expected.into();
+        self.scan_with(|scanner| Scanner::error(scanner, expected.as_str()))
+    }
+
+    /// Scan for a constant string, or return None if it does not come next
+    fn try_scan_string(&mut self, s: &str) -> Option<Token<'_>> {
+        self.scan_token(|scanner| scanner.try_scan_string(s).ok_or(()))
+            .ok()
+    }
+
+    /// Scan for a constant string, or return an error if it does not come next
+    fn scan_string(&mut self, s: &str) -> Result<Token<'_>, ScanError> {
+        self.scan_token(|scanner| scanner.scan_string(s))
+    }
+
+    /// Scan for a regular expression, or return None if it does not match
+    fn try_scan_regex(&mut self, regex: impl RegexSource) -> Option<Token<'_>> {
+        self.scan_token(|scanner| scanner.try_scan_regex(&regex).ok_or(()))
+            .ok()
+    }
+
+    /// Scan for a regular expression, or return an error if it does not match
+    fn scan_regex(&mut self, regex: impl RegexSource) -> Result<Token<'_>, ScanError> {
+        self.scan_token(|scanner| scanner.scan_regex(&regex))
+    }
+
+    /// Scan for a linebreak, or return None if there is none
+    fn try_scan_linebreak(&mut self) -> Option<Token<'_>> {
+        self.scan_token(|scanner| scanner.try_scan_linebreak().ok_or(()))
+            .ok()
+    }
+
+    /// Scan for a linebreak, or return an error if there is none
+    fn scan_linebreak(&mut self) -> Result<Token<'_>, ScanError> {
+        self.scan_token(|scanner| scanner.scan_linebreak())
+    }
+
+    /// Scan for a signed integer, or return None if there is none
+    fn try_scan_signed_int(&mut self) -> Option<Token<'_>> {
+        self.scan_token(|scanner| scanner.try_scan_signed_int().ok_or(()))
+            .ok()
+    }
+
+    /// Scan for a signed integer, or return an error if there is none
+    fn scan_signed_int(&mut self) -> Result<Token<'_>, ScanError> {
+        self.scan_token(|scanner| scanner.scan_signed_int())
+    }
+
+    /// Scan for an unsigned integer, or return None if there is none
+    fn try_scan_unsigned_int(&mut self) -> Option<Token<'_>> {
+        self.scan_token(|scanner| scanner.try_scan_unsigned_int().ok_or(()))
+            .ok()
+    }
+
+    /// Scan for an unsigned integer, or return an error if there is none
+    fn scan_unsigned_int(&mut self) -> Result<Token<'_>, ScanError> {
+        self.scan_token(|scanner| scanner.scan_unsigned_int())
+    }
+
+    /// Scan for a decimal integer and convert it, or return None if there is no integer next
+    fn try_scan_int<T: ScanInt>(&mut self) -> Option<Result<T, ScanError>> {
+        self.scan_with(|scanner| scanner.try_scan_int())
+    }
+
+    /// Scan for a decimal integer and convert it, or return an error if there is none or it does not fit in `T`
+    fn scan_int<T: ScanInt>(&mut self) -> Result<T, ScanError> {
+        self.scan_with(|scanner| scanner.scan_int())
+    }
+
+    /// Scan for an integer in a radix from 2 to 36 and convert it, or return None if there is no integer next
+    ///
+    /// Panics if the radix is out of range.
+    fn try_scan_int_radix<T: ScanInt>(&mut self, radix: u32) -> Option<Result<T, ScanError>> {
+        self.scan_with(|scanner| scanner.try_scan_int_radix(radix))
+    }
+
+    /// Scan for an integer in a radix from 2 to 36 and convert it, or return an error if there is none or it does not fit in `T`
+    ///
+    /// Panics if the radix is out of range.
+    fn scan_int_radix<T: ScanInt>(&mut self, radix: u32) -> Result<T, ScanError> {
+        self.scan_with(|scanner| scanner.scan_int_radix(radix))
+    }
+
+    /// Scan for a decimal integer whose digits may be split into groups by runs of a separator,
+    /// or return None if there is no integer next
+    fn try_scan_int_grouped<T: ScanInt>(
+        &mut self,
+        separator: char,
+    ) -> Option<Result<T, ScanError>> {
+        self.scan_with(|scanner| scanner.try_scan_int_grouped(separator))
+    }
+
+    /// Scan for a decimal integer whose digits may be split into groups by runs of a separator,
+    /// or return an error if there is none or it does not fit in `T`
+    fn scan_int_grouped<T: ScanInt>(&mut self, separator: char) -> Result<T, ScanError> {
+        self.scan_with(|scanner| scanner.scan_int_grouped(separator))
+    }
+
+    /// Scan for a floating-point number, or return None if there is none
+    fn try_scan_float(&mut self) -> Option<Token<'_>> {
+        self.scan_token(|scanner| scanner.try_scan_float().ok_or(()))
+            .ok()
+    }
+
+    /// Scan for a floating-point number, or return an error if there is none
+    fn scan_float(&mut self) -> Result<Token<'_>, ScanError> {
+        self.scan_token(|scanner| scanner.scan_float())
+    }
+
+    /// Scan for a single character that satisfies a predicate, or return None if the next one does not
+    fn try_scan_char_if(&mut self, predicate: impl Fn(char) -> bool) -> Option<Token<'_>> {
+        self.scan_token(|scanner| scanner.try_scan_char_if(&predicate).ok_or(()))
+            .ok()
+    }
+
+    /// Scan for a single character from a set, or return None if the next one is not in it
+    fn try_scan_one_of(&mut self, chars: &str) -> Option<Token<'_>> {
+        self.scan_token(|scanner| scanner.try_scan_one_of(chars).ok_or(()))
+            .ok()
+    }
+
+    /// Scan for a run of one or more characters that satisfy a predicate, or return None if there is none
+    fn try_scan_while(&mut self, predicate: impl Fn(char) -> bool) -> Option<Token<'_>> {
+        self.scan_token(|scanner| scanner.try_scan_while(&predicate).ok_or(()))
+            .ok()
+    }
+
+    /// Scan for a word of letters, digits and underscores, or return None if there is none
+    fn try_scan_word(&mut self) -> Option<Token<'_>> {
+        self.scan_token(|scanner| scanner.try_scan_word().ok_or(()))
+            .ok()
+    }
+
+    /// Scan a value that implements `Scan`
+    fn scan_value<T: for<'b> Scan<'b>>(&mut self) -> Result<T, ScanError> {
+        self.scan_with(|scanner| T::scan(scanner))
+    }
+
+    /// Scan values one after another until the end of the input, stopping after the first error
+    fn scan_iter<T: for<'b> Scan<'b>>(self) -> ScanIter<Self, T>
+    where
+        Self: Sized,
+    {
+        ScanIter {
+            scanner: self,
+            failed: false,
+            _value: std::marker::PhantomData,
+        }
+    }
+}
+
+impl TokenScanner for Scanner<'_> {
+    fn is_finished(&mut self) -> bool {
+        Scanner::is_finished(self)
+    }
+
+    fn error(&mut self, expected: impl Into<String>) -> ScanError {
+        Scanner::error(self, expected)
+    }
+
+    fn scan_with<T>(&mut self, mut f: impl FnMut(&mut Scanner<'_>) -> T) -> T {
+        f(self)
+    }
+
+    fn scan_token<E>(
+        &mut self,
+        f: impl for<'b> Fn(&mut Scanner<'b>) -> Result<Token<'b>, E>,
+    ) -> Result<Token<'_>, E> {
+        f(self)
+    }
+}
+
+impl<S: TokenScanner> TokenScanner for &mut S {
+    fn is_finished(&mut self) -> bool {
+        S::is_finished(self)
+    }
+
+    fn error(&mut self, expected: impl Into<String>) -> ScanError {
+        S::error(self, expected)
+    }
+
+    fn scan_with<T>(&mut self, f: impl FnMut(&mut Scanner<'_>) -> T) -> T {
+        S::scan_with(self, f)
+    }
+
+    fn scan_token<E>(
+        &mut self,
+        f: impl for<'b> Fn(&mut Scanner<'b>) -> Result<Token<'b>, E>,
+    ) -> Result<Token<'_>, E> {
+        S::scan_token(self, f)
+    }
+}
+
+/// An iterator over the values read by `TokenScanner::scan_iter`
+pub struct ScanIter<S, T> {
+    scanner: S,
+    failed: bool,
+    _value: std::marker::PhantomData<T>,
+}
+
+impl<S: TokenScanner, T: for<'b> Scan<'b>> Iterator for ScanIter<S, T> {
+    type Item = Result<T, ScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.scanner.is_finished() {
+            return None;
+        }
+        let result = self.scanner.scan_value();
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm::IVec2;
+
+    use super::*;
+
+    /// Scan an integer from the start of `input` and return it with the offset the scanner stopped at
+    fn int<T: ScanInt>(input: &str, radix: u32, separator: Option<char>) -> (Option<T>, usize) {
+        let mut scanner = Scanner::new(input, false);
+        let value = scanner
+            .try_scan_int_with::<T>(radix, separator)
+            .and_then(Result::ok);
+        (value, scanner.offset())
+    }
+
+    #[test]
+    fn int_boundaries() {
+        assert_eq!(int::<u8>("255", 10, None), (Some(255), 3));
+        assert_eq!(int::<i8>("-128", 10, None), (Some(-128), 4));
+        assert_eq!(int::<i8>("127", 10, None), (Some(127), 3));
+        assert_eq!(
+            int::<i128>("-170141183460469231731687303715884105728", 10, None).0,
+            Some(i128::MIN)
+        );
+    }
+
+    #[test]
+    fn int_overflow() {
+        for (input, expected) in [("256", "u8"), ("128", "i8"), ("-129", "i8")] {
+            let mut scanner = Scanner::new(input, false);
+            let error = match expected {
+                "u8" => scanner.scan_int::<u8>().unwrap_err(),
+                _ => scanner.scan_int::<i8>().unwrap_err(),
+            };
+            assert_eq!(error.offset(), 0);
+            assert!(error.expected().contains(expected), "{}", error.expected());
+            assert_eq!(scanner.offset(), 0);
+        }
+    }
+
+    #[test]
+    fn int_sign_only_for_signed_types() {
+        assert_eq!(int::<u32>("-5", 10, None), (None, 0));
+        assert_eq!(int::<i32>("-", 10, None), (None, 0));
+        assert_eq!(int::<i32>("-x", 10, None), (None, 0));
+    }
+
+    #[test]
+    fn int_radix() {
+        assert_eq!(int::<u32>("ff", 16, None), (Some(255), 2));
+        assert_eq!(int::<i32>("-0x1F,", 16, None), (Some(-31), 5));
+        assert_eq!(int::<u8>("1012", 2, None), (Some(5), 3));
+        assert_eq!(int::<u8>("0b110", 2, None), (Some(6), 5));
+        // A prefix with no digits after it is just a zero
+        assert_eq!(int::<u8>("0bx", 2, None), (Some(0), 1));
+        assert_eq!(int::<u8>("z", 16, None), (None, 0));
+    }
+
+    #[test]
+    fn int_radix_limits() {
+        assert_eq!(int::<u64>("zz", 36, None), (Some(36 * 36 - 1), 2));
+        assert_eq!(int::<u8>("21", 2, None), (None, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "radix 37 is not in the range 2 to 36")]
+    fn int_radix_too_large() {
+        Scanner::new("10", false).scan_int_radix::<u32>(37).ok();
+    }
+
+    #[test]
+    #[should_panic(expected = "radix 1 is not in the range 2 to 36")]
+    fn int_radix_too_small() {
+        Scanner::new("10", false).scan_int_radix::<u32>(1).ok();
+    }
+
+    #[test]
+    fn int_grouped() {
+        assert_eq!(int::<u32>("1_000_000", 10, Some('_')), (Some(1_000_000), 9));
+        assert_eq!(int::<u32>("1__000", 10, Some('_')), (Some(1000), 6));
+        assert_eq!(int::<u32>("7  15   30", 10, Some(' ')), (Some(71530), 10));
+        assert_eq!(int::<i32>("-1_5", 10, Some('_')), (Some(-15), 4));
+    }
+
+    #[test]
+    fn int_grouped_separators_need_digits_around_them() {
+        assert_eq!(int::<u32>("_5", 10, Some('_')), (None, 0));
+        assert_eq!(int::<u32>("12_ ", 10, Some('_')), (Some(12), 2));
+        assert_eq!(int::<u32>("12__", 10, Some('_')), (Some(12), 2));
+        assert_eq!(int::<i32>("-_5", 10, Some('_')), (None, 0));
+    }
+
+    #[test]
+    fn int_big() {
+        let digits = "-123456789012345678901234567890123456789012345678901234567890";
+        let (value, offset) = int::<BigInt>(digits, 10, None);
+        assert_eq!(value, digits.parse::<BigInt>().ok());
+        assert_eq!(offset, digits.len());
+        assert_eq!(int::<BigUint>("-1", 10, None), (None, 0));
+        assert_eq!(
+            int::<BigUint>("1_000_000_000_000_000_000_000", 10, Some('_')).0,
+            "1000000000000000000000".parse::<BigUint>().ok()
+        );
+    }
+
+    /// A line with its fields in a different order from the struct, such as `move 3 from 1 to 2`
+    #[derive(Debug, PartialEq, Scan)]
+    #[scan("move {count} from {from} to {to}")]
+    struct Move {
+        from: u32,
+        to: u32,
+        count: u32,
+    }
+
+    #[derive(Debug, PartialEq, Scan)]
+    #[scan("{name}: {values: sep(',')} | {rest: sep(' ')} end")]
+    struct Lists {
+        name: String,
+        values: Vec<i32>,
+        rest: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq, Scan)]
+    enum Instruction {
+        #[scan("noop")]
+        Noop,
+        #[scan("add {value}")]
+        Add { value: i32 },
+        #[scan("add {x} , {y}")]
+        AddPair { x: i32, y: i32 },
+    }
+
+    fn scan<T: for<'s> Scan<'s>>(input: &str) -> (Result<T, ScanError>, usize) {
+        let mut scanner = Scanner::new(input, true);
+        let result = T::scan(&mut scanner);
+        (result, scanner.offset())
+    }
+
+    #[test]
+    fn derive_struct_fields_in_format_order() {
+        let (result, offset) = scan::<Move>("move 3 from 1 to 2\nmove");
+        let expected = Move {
+            from: 1,
+            to: 2,
+            count: 3,
+        };
+        assert_eq!(result.unwrap(), expected);
+        assert_eq!(offset, 18);
+    }
+
+    #[test]
+    fn derive_struct_lists() {
+        let (result, _) = scan::<Lists>("abc: 1, -2,3 | 4  5 6 end");
+        let expected = Lists {
+            name: "abc".to_string(),
+            values: vec![1, -2, 3],
+            rest: vec![4, 5, 6],
+        };
+        assert_eq!(result.unwrap(), expected);
+    }
+
+    #[test]
+    fn derive_struct_errors() {
+        let error = scan::<Move>("move 3 from x to 2").0.unwrap_err();
+        assert_eq!((error.offset(), error.column()), (12, 13));
+        assert_eq!(error.expected(), "integer");
+
+        let error = scan::<Move>("move 3 to 2").0.unwrap_err();
+        assert_eq!(error.offset(), 7);
+
+        // A list item that fails before the literal that ends the list is reported
+        let error = scan::<Lists>("abc: 1 | 4 x end").0.unwrap_err();
+        assert_eq!(error.offset(), 11);
+
+        let error = scan::<Lists>("abc: 1 | 4 300 end").0.unwrap_err();
+        assert_eq!(error.offset(), 11);
+    }
+
+    #[test]
+    fn derive_whitespace_is_required() {
+        let (result, offset) = scan::<Move>("move 3 from 1  to\t2 ");
+        assert_eq!(result.unwrap().to, 2);
+        assert_eq!(offset, 19);
+
+        let error = scan::<Move>("move 3 from 1to 2").0.unwrap_err();
+        assert_eq!((error.offset(), error.expected()), (13, "whitespace"));
+        let error = scan::<Move>("move3 from 1 to 2").0.unwrap_err();
+        assert_eq!((error.offset(), error.expected()), (4, "whitespace"));
+        let error = scan::<Lists>("abc: 1|4 end").0.unwrap_err();
+        assert_eq!((error.offset(), error.expected()), (6, "whitespace"));
+        assert!(scan::<Lists>("abc:1 | 4 end").0.is_err());
+    }
+
+    #[test]
+    fn derive_enum_variants() {
+        assert_eq!(scan::<Instruction>("noop").0.unwrap(), Instruction::Noop);
+        assert_eq!(
+            scan::<Instruction>("add -4").0.unwrap(),
+            Instruction::Add { value: -4 }
+        );
+    }
+
+    #[test]
+    fn derive_enum_variants_in_order() {
+        // `add {value}` matches first and leaves the rest of the pair unscanned
+        let (result, offset) = scan::<Instruction>("add 1 , 2");
+        assert_eq!(result.unwrap(), Instruction::Add { value: 1 });
+        assert_eq!(offset, 5);
+    }
+
+    #[test]
+    fn derive_enum_reports_furthest_error() {
+        let (result, offset) = scan::<Instruction>("add x");
+        let error = result.unwrap_err();
+        assert_eq!((error.offset(), error.expected()), (4, "integer"));
+        assert_eq!(offset, 0);
+
+        let error = scan::<Instruction>("jump 3").0.unwrap_err();
+        assert_eq!(error.offset(), 0);
+    }
+
+    #[test]
+    fn grid_blocks() {
+        let mut scanner = Scanner::new("#.#\n..#\n\n##\n", true);
+        let cell = |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        };
+        let grid = scanner.scan_grid(cell).unwrap();
+        assert_eq!(grid.size(), IVec2::new(3, 2));
+        assert_eq!(grid.positions(|&wall| wall).count(), 3);
+        assert_eq!(scanner.scan_grid(cell).unwrap().size(), IVec2::new(2, 1));
+
+        let error = Scanner::new("#.\n#x\n", true).scan_grid(cell).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 2));
+        let error = Scanner::new("#.\n#\n", true).scan_grid(cell).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 1));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Assignment {
+        name: String,
+        value: i32,
+    }
+
+    impl<'s> FromCaptures<'s> for Assignment {
+        fn from_captures(captures: &ScanCaptures<'s>) -> Result<Self, ScanError> {
+            Ok(Assignment {
+                name: captures.require("name")?.as_str().to_string(),
+                value: captures.parse("value")?,
+            })
+        }
+    }
+
+    #[test]
+    fn capture_spans() {
+        let mut scanner = Scanner::new("x = 1; yy = 22", true);
+        scanner.scan_string("x = 1;").unwrap();
+        let captures = scanner
+            .try_scan_regex_named(r"(?P<name>\w+) = (?P<value>\d+)")
+            .unwrap();
+        assert_eq!(captures.whole().offset(), 7);
+        assert_eq!(captures.whole().as_str(), "yy = 22");
+        let name = captures.name("name").unwrap();
+        assert_eq!((name.as_str(), name.offset()), ("yy", 7));
+        let value = captures.get(2).unwrap();
+        assert_eq!((value.as_str(), value.offset()), ("22", 12));
+        assert!(captures.get(0).is_none());
+        assert!(captures.get(3).is_none());
+        assert!(scanner.is_finished());
+    }
+
+    #[test]
+    fn capture_spans_with_origin() {
+        let origin = Origin {
+            offset: 100,
+            line: 4,
+        };
+        let mut scanner = Scanner::with_origin("ab\nkey: 42\n", 3, false, origin);
+        let [key, value] = scanner
+            .try_scan_regex_captures::<2>(r"(\w+): (\d+)")
+            .unwrap();
+        assert_eq!((key.offset(), value.offset()), (103, 108));
+        let error = value.error("a digit");
+        assert_eq!((error.offset(), error.line(), error.column()), (108, 6, 6));
+    }
+
+    #[test]
+    fn capture_names() {
+        let mut scanner = Scanner::new("5 x", false);
+        let captures = scanner
+            .try_scan_regex_named(r"(?P<sign>-)?(?P<digits>\d+)")
+            .unwrap();
+        assert!(captures.name("sign").is_none());
+        assert!(captures.name("other").is_none());
+        assert_eq!(captures.parse::<u32>("digits").unwrap(), 5);
+
+        let error = captures.require("sign").unwrap_err();
+        assert_eq!(error.expected(), "a value for 'sign'");
+        assert_eq!(error.offset(), 0);
+        let error = captures.parse::<u32>("sign").unwrap_err();
+        assert_eq!(error.expected(), "a value for 'sign'");
+    }
+
+    #[test]
+    fn capture_parse_error_points_at_group() {
+        let mut scanner = Scanner::new("a = b", false);
+        let captures = scanner
+            .try_scan_regex_named(r"(?P<name>\w+) = (?P<value>\w+)")
+            .unwrap();
+        let error = captures.parse::<i32>("value").unwrap_err();
+        assert_eq!((error.offset(), error.column()), (4, 5));
+        assert!(error.expected().contains("'b'"), "{}", error.expected());
+    }
+
+    #[test]
+    fn captures_as() {
+        let regex = r"(?P<name>[a-z]+)=(?P<value>-?\d+)";
+        let mut scanner = Scanner::new("abc=-12\nd=x", true);
+        let assignment = scanner.try_scan_regex_as::<Assignment>(regex).unwrap();
+        assert_eq!(
+            assignment.unwrap(),
+            Assignment {
+                name: "abc".to_string(),
+                value: -12
+            }
+        );
+        assert!(scanner.try_scan_regex_as::<Assignment>(regex).is_none());
+        assert_eq!(scanner.offset(), 7);
+
+        let error = scanner
+            .scan_regex_as::<Assignment>(r"(?P<name>[a-z]+)=(?P<value>\w+)")
+            .unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 3));
+    }
+
+    #[test]
+    fn optional_captures() {
+        let regex = r"(-)?(\d+)";
+        let mut scanner = Scanner::new("5 -6", true);
+        let [sign, digits] = scanner
+            .try_scan_regex_optional_captures::<2>(regex)
+            .unwrap();
+        assert!(sign.is_none());
+        assert_eq!(digits.unwrap().as_str(), "5");
+        let [sign, digits] = scanner
+            .try_scan_regex_optional_captures::<2>(regex)
+            .unwrap();
+        assert_eq!(sign.unwrap().offset(), 2);
+        assert_eq!(digits.unwrap().offset(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "a group in regex '(-)?(\\d+)' did not match")]
+    fn missing_required_capture() {
+        Scanner::new("5", false).try_scan_regex_captures::<2>(r"(-)?(\d+)");
+    }
+
+    #[test]
+    #[should_panic(expected = "has the wrong number of groups")]
+    fn wrong_number_of_captures() {
+        Scanner::new("5", false).try_scan_regex_captures::<2>(r"(\d+)");
+    }
+
+    #[test]
+    fn checkpoint_and_restore() {
+        let mut scanner = Scanner::new("move 3 left", true);
+        let checkpoint = scanner.checkpoint();
+        scanner.scan_string("move").unwrap();
+        scanner.scan_int::<u32>().unwrap();
+        assert_eq!(scanner.offset(), 6);
+        scanner.restore(checkpoint);
+        assert_eq!(scanner.offset(), 0);
+        assert_eq!(scanner.checkpoint(), checkpoint);
+        assert!(scanner.try_scan_string("move").is_some());
+        assert!(scanner.checkpoint() > checkpoint);
+    }
+
+    #[test]
+    fn failed_scan_leaves_offset() {
+        let mut scanner = Scanner::new("a 12", true);
+        scanner.scan_string("a").unwrap();
+        assert!(scanner.try_scan_string("b").is_none());
+        assert!(scanner.try_scan_regex("[a-z]+").is_none());
+        assert!(scanner.scan_int::<u8>().is_ok());
+        assert_eq!(scanner.offset(), 4);
+    }
+
+    #[test]
+    fn transaction() {
+        let mut scanner = Scanner::new("1 2 x", true);
+        let pair = scanner.transaction(|scanner| {
+            let a = scanner.try_scan_int::<u32>()?.ok()?;
+            let b = scanner.try_scan_int::<u32>()?.ok()?;
+            Some((a, b))
+        });
+        assert_eq!(pair, Some((1, 2)));
+        assert_eq!(scanner.offset(), 3);
+
+        let rolled_back = scanner.transaction(|scanner| {
+            scanner.try_scan_string("x")?;
+            scanner.try_scan_string("y")
+        });
+        assert!(rolled_back.is_none());
+        assert_eq!(scanner.offset(), 3);
+        assert_eq!(scanner.scan_string("x").unwrap().offset(), 4);
+    }
+
+    #[test]
+    fn peeking_consumes_nothing() {
+        let mut scanner = Scanner::new("  seeds: 79", true);
+        let token = scanner.peek_string("seeds:").unwrap();
+        assert_eq!((token.as_str(), token.offset()), ("seeds:", 2));
+        assert!(scanner.peek_string("soil").is_none());
+        let token = scanner.peek_regex(r"\w+").unwrap();
+        assert_eq!((token.as_str(), token.offset()), ("seeds", 2));
+        assert!(scanner.peek_regex(r"\d+").is_none());
+        let value = scanner.peek(|scanner| {
+            scanner.try_scan_string("seeds:")?;
+            scanner.try_scan_int::<u32>()?.ok()
+        });
+        assert_eq!(value, Some(79));
+        assert_eq!(scanner.offset(), 0);
+        assert!(scanner.try_scan_string("seeds:").is_some());
+    }
+
+    #[test]
+    fn error_position_on_later_line() {
+        let error = ScanError::new("a digit", "ab\ncd\r\nef x", 10);
+        assert_eq!((error.offset(), error.line(), error.column()), (10, 3, 4));
+        let error = ScanError::new("a digit", "ab\ncd\r\nef x", 4);
+        assert_eq!((error.line(), error.column()), (2, 2));
+        assert_eq!(error.to_string(), "expected a digit at line 2, column 2");
+    }
+
+    #[test]
+    fn error_columns_count_characters() {
+        let error = ScanError::new("x", "\tab", 2);
+        assert_eq!(error.column(), 3);
+        let error = ScanError::new("x", "né 1", 4);
+        assert_eq!(error.column(), 4);
+        // An offset inside a character moves back to its start
+        let error = ScanError::new("x", "né 1", 2);
+        assert_eq!((error.offset(), error.column()), (1, 2));
+    }
+
+    #[test]
+    fn error_render() {
+        let error = ScanError::new("unsigned integer", "32T3K 765\n32T3K 7a5\n", 17);
+        assert_eq!(
+            error.render(),
+            [
+                " --> line 2, column 8",
+                "  |",
+                "2 | 32T3K 7a5",
+                "  |        ^ expected unsigned integer",
+            ]
+            .join("\n")
+        );
+
+        let input = format!("{}\t\u{e9}x", "\n".repeat(9));
+        let error = ScanError::new("y", &input, input.len() - 1);
+        assert_eq!(
+            error.render(),
+            [
+                "  --> line 10, column 3",
+                "   |",
+                "10 | \t\u{e9}x",
+                "   | \t ^ expected y",
+            ]
+            .join("\n")
+        );
+    }
+
+    fn digits() -> &'static Pattern {
+        pattern!(r"\d+")
+    }
+
+    #[test]
+    fn pattern_compiles_once() {
+        assert!(std::ptr::eq(digits(), digits()));
+        let regex = digits().regex().unwrap();
+        assert!(std::ptr::eq(regex, digits().regex().unwrap()));
+        let shared = std::thread::spawn(|| digits().regex().unwrap() as *const Regex as usize);
+        assert_eq!(shared.join().unwrap(), regex as *const Regex as usize);
+
+        let mut scanner = Scanner::new("12 34", true);
+        assert_eq!(scanner.scan_regex(digits()).unwrap().as_str(), "12");
+        assert_eq!(scanner.scan_regex(digits()).unwrap().as_str(), "34");
+    }
+
+    #[test]
+    fn string_patterns_are_cached_per_thread() {
+        let cached = || REGEX_CACHE.with(|cache| cache.borrow().len());
+        let address = |pattern: &str| {
+            pattern
+                .with_regex(|regex| regex as *const Regex as usize)
+                .unwrap()
+        };
+        let before = cached();
+        let first = address("[a-z]+;");
+        assert_eq!(cached(), before + 1);
+        let mut scanner = Scanner::new("ab;cd;", false);
+        scanner.scan_regex("[a-z]+;").unwrap();
+        scanner.scan_regex(String::from("[a-z]+;")).unwrap();
+        assert_eq!(cached(), before + 1);
+        assert_eq!(address("[a-z]+;"), first);
+    }
+
+    #[test]
+    fn invalid_regex_is_reported() {
+        let mut scanner = Scanner::new("(a)", false);
+        assert!(scanner.try_scan_regex("(a").is_none());
+        assert!(scanner.peek_regex("(a").is_none());
+        assert!(scanner.try_scan_regex_named("(a").is_none());
+        let error = scanner.scan_regex("(a").unwrap_err();
+        assert_eq!(
+            error.expected(),
+            "regex '(a' to be valid (error: unclosed group)"
+        );
+        assert_eq!(error.offset(), 0);
+        assert_eq!(scanner.offset(), 0);
+
+        static INVALID: Pattern = Pattern::new("[z-a]");
+        assert!(INVALID.regex().is_err());
+        let error = scanner.scan_regex_named(&INVALID).unwrap_err();
+        assert!(error.expected().starts_with("regex '[z-a]' to be valid"));
+        assert!(scanner.scan_regex(r"\(\w").is_ok());
+    }
+
+    #[test]
+    fn scan_string_regex() {
+        let separator = ';';
+        let regex = format!(r"\d+{}", separator);
+        let mut scanner = Scanner::new("12;3,4;", false);
+        assert_eq!(scanner.scan_regex(regex.clone()).unwrap().as_str(), "12;");
+        assert!(scanner.try_scan_regex(&regex).is_none());
+        assert_eq!(scanner.offset(), 3);
+    }
+
+    #[test]
+    fn scan_arc_regex() {
+        let regex: Arc<str> = Arc::from(r"[A-Z]{3}");
+        let mut scanner = Scanner::new("AAA = (BBB, CCC)", true);
+        assert_eq!(scanner.scan_regex(regex.clone()).unwrap().offset(), 0);
+        assert!(scanner.try_scan_regex(&regex).is_none());
+        scanner.scan_string("= (").unwrap();
+        assert_eq!(scanner.scan_regex(&regex).unwrap().as_str(), "BBB");
+    }
+
+    #[test]
+    fn scan_precompiled_regex() {
+        let regex = Regex::new(r"^#+").unwrap();
+        let mut scanner = Scanner::new(".##.#", false);
+        // A match further on does not count
+        assert!(scanner.try_scan_regex(&regex).is_none());
+        scanner.scan_string(".").unwrap();
+        let token = scanner.scan_regex(&regex).unwrap();
+        assert_eq!((token.as_str(), token.offset()), ("##", 1));
+        let error = scanner.scan_regex(regex).unwrap_err();
+        assert_eq!(error.expected(), "match for regex '^#+'");
+    }
+
+    #[test]
+    fn sections() {
+        let input = "\nseeds: 79 14\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\n\nsoil:\n";
+        let mut scanner = Scanner::new(input, true);
+
+        let section = scanner.scan_section().unwrap();
+        assert!(section.header().is_none());
+        assert_eq!(section.whole().as_str(), "seeds: 79 14");
+        assert_eq!(section.body().as_str(), "seeds: 79 14");
+        assert_eq!(section.offset(), 1);
+
+        let section = scanner.scan_section().unwrap();
+        assert_eq!(section.header().unwrap().as_str(), "seed-to-soil map:");
+        assert_eq!(section.body().as_str(), "50 98 2\n52 50 48");
+        assert_eq!((section.offset(), section.body().offset()), (15, 33));
+        let mut body = section.scanner(true);
+        let values = std::iter::from_fn(|| body.try_scan_int::<u32>().map(Result::unwrap));
+        assert_eq!(values.collect::<Vec<_>>(), [50, 98, 2, 52, 50, 48]);
+        let error = body.error("a digit");
+        assert_eq!((error.offset(), error.line(), error.column()), (49, 6, 9));
+
+        // A header with nothing under it has an empty body at its end
+        let section = scanner.scan_section().unwrap();
+        assert_eq!(section.header().unwrap().as_str(), "soil:");
+        assert_eq!((section.body().len(), section.body().offset()), (0, 57));
+        assert!(scanner.try_scan_section().is_none());
+        assert!(scanner.is_finished());
+    }
+
+    #[test]
+    fn last_section_without_blank_line() {
+        let mut scanner = Scanner::new("#.\n.#\n\n..\r\n##", false);
+        assert_eq!(scanner.scan_section().unwrap().whole().as_str(), "#.\n.#");
+        let section = scanner.scan_section().unwrap();
+        assert_eq!(section.whole().as_str(), "..\r\n##");
+        assert_eq!(section.offset(), 7);
+        assert!(scanner.is_finished());
+
+        let error = scanner.scan_section().unwrap_err();
+        assert_eq!(error.offset(), 13);
     }
 }
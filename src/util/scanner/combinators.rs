@@ -0,0 +1,440 @@
+//! Parser combinators built on `Scanner`
+//!
+//! A parser is anything that implements `Parser`: every function or closure of the form
+//! `Fn(&mut Scanner) -> Result<T, ScanError>`, and tuples of parsers, which run in sequence and
+//! return a tuple of their results. Combinators that try a parser and carry on if it fails, such
+//! as `opt`, `alt` and `many0`, rewind the scanner to where the attempt started.
+
+use std::str::FromStr;
+
+use super::{RegexSource, ScanError, Scanner, Token};
+
+/// Something that can parse a `T` from a scanner
+pub trait Parser<'s, T> {
+    /// Parse a value, advancing the scanner past it
+    fn parse(&self, scanner: &mut Scanner<'s>) -> Result<T, ScanError>;
+}
+
+impl<'s, T, F> Parser<'s, T> for F
+where
+    F: Fn(&mut Scanner<'s>) -> Result<T, ScanError>,
+{
+    fn parse(&self, scanner: &mut Scanner<'s>) -> Result<T, ScanError> {
+        self(scanner)
+    }
+}
+
+macro_rules! impl_sequence {
+    ($($parser:ident $value:ident $index:tt),+) => {
+        impl<'s, $($value, $parser: Parser<'s, $value>),+> Parser<'s, ($($value,)+)>
+            for ($($parser,)+)
+        {
+            fn parse(&self, scanner: &mut Scanner<'s>) -> Result<($($value,)+), ScanError> {
+                Ok(($(self.$index.parse(scanner)?,)+))
+            }
+        }
+    };
+}
+
+impl_sequence!(P0 T0 0, P1 T1 1);
+impl_sequence!(P0 T0 0, P1 T1 1, P2 T2 2);
+impl_sequence!(P0 T0 0, P1 T1 1, P2 T2 2, P3 T3 3);
+impl_sequence!(P0 T0 0, P1 T1 1, P2 T2 2, P3 T3 3, P4 T4 4);
+impl_sequence!(P0 T0 0, P1 T1 1, P2 T2 2, P3 T3 3, P4 T4 4, P5 T5 5);
+
+/// A list of alternative parsers, tried in order by `alt`
+pub trait Alternatives<'s, T> {
+    /// Return the result of the first parser that succeeds, or the error that got furthest
+    fn parse_first(&self, scanner: &mut Scanner<'s>) -> Result<T, ScanError>;
+}
+
+macro_rules! impl_alternatives {
+    ($($parser:ident $index:tt),+) => {
+        impl<'s, T, $($parser: Parser<'s, T>),+> Alternatives<'s, T> for ($($parser,)+) {
+            fn parse_first(&self, scanner: &mut Scanner<'s>) -> Result<T, ScanError> {
+                let start = scanner.checkpoint();
+                let mut furthest: Option<ScanError> = None;
+                $(
+                    match self.$index.parse(scanner) {
+                        Ok(value) => return Ok(value),
+                        Err(err) => {
+                            scanner.restore(start);
+                            if furthest.as_ref().is_none_or(|f| err.offset() > f.offset()) {
+                                furthest = Some(err);
+                            }
+                        }
+                    }
+                )+
+                Err(furthest.unwrap())
+            }
+        }
+    };
+}
+
+impl_alternatives!(P0 0, P1 1);
+impl_alternatives!(P0 0, P1 1, P2 2);
+impl_alternatives!(P0 0, P1 1, P2 2, P3 3);
+impl_alternatives!(P0 0, P1 1, P2 2, P3 3, P4 4);
+impl_alternatives!(P0 0, P1 1, P2 2, P3 3, P4 4, P5 5);
+
+/// Match a constant string
+pub fn tag<'s>(s: &'static str) -> impl Parser<'s, Token<'s>> {
+    move |scanner: &mut Scanner<'s>| scanner.scan_string(s)
+}
+
+/// Match a regular expression
+pub fn regex<'s>(regex: impl RegexSource) -> impl Parser<'s, Token<'s>> {
+    move |scanner: &mut Scanner<'s>| scanner.scan_regex(&regex)
+}
+
+/// Match a linebreak
+pub fn linebreak<'s>() -> impl Parser<'s, Token<'s>> {
+    |scanner: &mut Scanner<'s>| scanner.scan_linebreak()
+}
+
+/// Match an unsigned integer and parse it as a `T`
+pub fn unsigned<'s, T: FromStr>() -> impl Parser<'s, T> {
+    |scanner: &mut Scanner<'s>| scanner.scan_unsigned_int()?.parse()
+}
+
+/// Match a signed integer and parse it as a `T`
+pub fn signed<'s, T: FromStr>() -> impl Parser<'s, T> {
+    |scanner: &mut Scanner<'s>| scanner.scan_signed_int()?.parse()
+}
+
+/// Transform the result of a parser
+pub fn map<'s, A, B>(parser: impl Parser<'s, A>, f: impl Fn(A) -> B) -> impl Parser<'s, B> {
+    move |scanner: &mut Scanner<'s>| parser.parse(scanner).map(&f)
+}
+
+/// Transform the result of a parser with a function that may fail
+///
+/// The function is given the token spanning everything the parser matched, for building errors.
+pub fn try_map<'s, A, B>(
+    parser: impl Parser<'s, A>,
+    f: impl Fn(A, &Token<'s>) -> Result<B, ScanError>,
+) -> impl Parser<'s, B> {
+    let parser = spanned(parser);
+    move |scanner: &mut Scanner<'s>| {
+        let (value, span) = parser.parse(scanner)?;
+        f(value, &span)
+    }
+}
+
+/// Return the result of a parser along with a token spanning the input it matched
+///
+/// Whitespace skipped before the first token is not part of the span.
+pub fn spanned<'s, T>(parser: impl Parser<'s, T>) -> impl Parser<'s, (T, Token<'s>)> {
+    move |scanner: &mut Scanner<'s>| {
+        let start = scanner.offset();
+        let value = parser.parse(scanner)?;
+        let end = scanner.offset();
+        let skipped =
+            scanner.string[start..end].len() - scanner.string[start..end].trim_start().len();
+        Ok((value, scanner.token(start + skipped, end)))
+    }
+}
+
+/// Try a parser, returning None and consuming nothing if it fails
+pub fn opt<'s, T>(parser: impl Parser<'s, T>) -> impl Parser<'s, Option<T>> {
+    move |scanner: &mut Scanner<'s>| {
+        let start = scanner.checkpoint();
+        match parser.parse(scanner) {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => {
+                scanner.restore(start);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Try each of a tuple of parsers in turn and return the result of the first that succeeds
+pub fn alt<'s, T>(alternatives: impl Alternatives<'s, T>) -> impl Parser<'s, T> {
+    move |scanner: &mut Scanner<'s>| alternatives.parse_first(scanner)
+}
+
+/// Append the results of a parser to `values` for as long as it succeeds, rewinding past the
+/// attempt that fails
+///
+/// Repetition also stops if the parser succeeds without consuming anything, which would otherwise
+/// loop forever.
+fn repeat<'s, T>(parser: &impl Parser<'s, T>, scanner: &mut Scanner<'s>, values: &mut Vec<T>) {
+    loop {
+        let start = scanner.checkpoint();
+        match parser.parse(scanner) {
+            Ok(value) if scanner.checkpoint() > start => values.push(value),
+            Ok(_) => break,
+            Err(_) => {
+                scanner.restore(start);
+                break;
+            }
+        }
+    }
+}
+
+/// Apply a parser as many times as it succeeds, possibly none
+pub fn many0<'s, T>(parser: impl Parser<'s, T>) -> impl Parser<'s, Vec<T>> {
+    move |scanner: &mut Scanner<'s>| {
+        let mut values = Vec::new();
+        repeat(&parser, scanner, &mut values);
+        Ok(values)
+    }
+}
+
+/// Apply a parser as many times as it succeeds, at least once
+pub fn many1<'s, T>(parser: impl Parser<'s, T>) -> impl Parser<'s, Vec<T>> {
+    move |scanner: &mut Scanner<'s>| {
+        let mut values = vec![parser.parse(scanner)?];
+        repeat(&parser, scanner, &mut values);
+        Ok(values)
+    }
+}
+
+/// Apply a parser repeatedly with a separator between each application, possibly none
+pub fn sep_by<'s, T, S>(
+    parser: impl Parser<'s, T>,
+    separator: impl Parser<'s, S>,
+) -> impl Parser<'s, Vec<T>> {
+    let parser = sep_by1(parser, separator);
+    move |scanner: &mut Scanner<'s>| {
+        let start = scanner.checkpoint();
+        parser.parse(scanner).or_else(|_| {
+            scanner.restore(start);
+            Ok(Vec::new())
+        })
+    }
+}
+
+/// Apply a parser repeatedly with a separator between each application, at least once
+///
+/// A separator that is not followed by another value is left unconsumed.
+pub fn sep_by1<'s, T, S>(
+    parser: impl Parser<'s, T>,
+    separator: impl Parser<'s, S>,
+) -> impl Parser<'s, Vec<T>> {
+    move |scanner: &mut Scanner<'s>| {
+        let mut values = vec![parser.parse(scanner)?];
+        let next = |scanner: &mut Scanner<'s>| {
+            separator.parse(scanner)?;
+            parser.parse(scanner)
+        };
+        repeat(&next, scanner, &mut values);
+        Ok(values)
+    }
+}
+
+/// Apply a parser between two others and return its result
+pub fn delimited<'s, O, T, C>(
+    open: impl Parser<'s, O>,
+    parser: impl Parser<'s, T>,
+    close: impl Parser<'s, C>,
+) -> impl Parser<'s, T> {
+    map((open, parser, close), |(_, value, _)| value)
+}
+
+/// Apply a parser after another and return its result
+pub fn preceded<'s, P, T>(
+    prefix: impl Parser<'s, P>,
+    parser: impl Parser<'s, T>,
+) -> impl Parser<'s, T> {
+    map((prefix, parser), |(_, value)| value)
+}
+
+/// Apply a parser before another and return its result
+pub fn terminated<'s, T, S>(
+    parser: impl Parser<'s, T>,
+    suffix: impl Parser<'s, S>,
+) -> impl Parser<'s, T> {
+    map((parser, suffix), |(value, _)| value)
+}
+
+/// An iterator that applies a parser repeatedly until the input is used up
+///
+/// The iterator ends after the first error, since the scanner cannot tell where the next value
+/// would start.
+pub struct ParseIter<'s, T, P> {
+    scanner: Scanner<'s>,
+    parser: P,
+    failed: bool,
+    _value: std::marker::PhantomData<T>,
+}
+
+impl<'s, T, P: Parser<'s, T>> ParseIter<'s, T, P> {
+    /// Create an iterator that parses values from a scanner
+    pub fn new(scanner: Scanner<'s>, parser: P) -> ParseIter<'s, T, P> {
+        ParseIter {
+            scanner,
+            parser,
+            failed: false,
+            _value: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'s, T, P: Parser<'s, T>> Iterator for ParseIter<'s, T, P> {
+    type Item = Result<T, ScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.scanner.is_finished() {
+            return None;
+        }
+        let result = self.parser.parse(&mut self.scanner);
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run a parser over `input` and return its result with the offset the scanner stopped at
+    fn run<'s, T>(parser: impl Parser<'s, T>, input: &'s str) -> (Result<T, ScanError>, usize) {
+        let mut scanner = Scanner::new(input, true);
+        let result = parser.parse(&mut scanner);
+        (result, scanner.offset())
+    }
+
+    /// Return the offset of the error a parser fails with on `input`
+    fn error_offset<'s, T: std::fmt::Debug>(parser: impl Parser<'s, T>, input: &'s str) -> usize {
+        run(parser, input).0.unwrap_err().offset()
+    }
+
+    #[test]
+    fn sequence() {
+        let (result, offset) = run(
+            (unsigned::<u32>(), tag("x"), unsigned::<u32>()),
+            "3x 4 rest",
+        );
+        assert_eq!(result.map(|(a, _, b)| (a, b)).unwrap(), (3, 4));
+        assert_eq!(offset, 4);
+        assert_eq!(error_offset((unsigned::<u32>(), tag("x")), "3 y"), 2);
+    }
+
+    #[test]
+    fn opt_rewinds() {
+        let (result, offset) = run(opt((tag("a"), tag("b"))), "a c");
+        assert_eq!(result.unwrap().map(|_| ()), None);
+        assert_eq!(offset, 0);
+        let (result, offset) = run(opt(tag("a")), "a c");
+        assert_eq!(result.unwrap().map(|token| token.as_str()), Some("a"));
+        assert_eq!(offset, 1);
+    }
+
+    #[test]
+    fn alt_backtracks() {
+        let parser = alt((
+            map((tag("a"), tag("b")), |_| 1),
+            map((tag("a"), tag("c")), |_| 2),
+        ));
+        let (result, offset) = run(parser, "a c");
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(offset, 3);
+    }
+
+    #[test]
+    fn alt_reports_furthest_error() {
+        let parser = alt((
+            map((tag("a"), tag("b"), tag("c")), |_| ()),
+            map(tag("x"), |_| ()),
+        ));
+        let (result, offset) = run(parser, "a b d");
+        assert_eq!(result.unwrap_err().offset(), 4);
+        assert_eq!(offset, 0);
+    }
+
+    #[test]
+    fn many_stops_at_failure() {
+        let pair = || map((tag("a"), tag("b")), |_| ());
+        let (result, offset) = run(many0(pair()), "ab ab a c");
+        assert_eq!(result.unwrap().len(), 2);
+        assert_eq!(offset, 5);
+
+        let (result, offset) = run(many0(pair()), "c");
+        assert!(result.unwrap().is_empty());
+        assert_eq!(offset, 0);
+
+        assert_eq!(run(many1(pair()), "ab").0.unwrap().len(), 1);
+        assert_eq!(error_offset(many1(pair()), "a c"), 2);
+    }
+
+    #[test]
+    fn many_stops_on_empty_match() {
+        let (result, offset) = run(many0(opt(tag("a"))), "a a b");
+        assert_eq!(result.unwrap().len(), 2);
+        assert_eq!(offset, 3);
+    }
+
+    #[test]
+    fn sep_by_leaves_trailing_separator() {
+        let (result, offset) = run(sep_by(unsigned::<u32>(), tag(",")), "1, 2,3, x");
+        assert_eq!(result.unwrap(), vec![1, 2, 3]);
+        assert_eq!(offset, 6);
+
+        let (result, offset) = run(sep_by(unsigned::<u32>(), tag(",")), "x");
+        assert!(result.unwrap().is_empty());
+        assert_eq!(offset, 0);
+    }
+
+    #[test]
+    fn sep_by1_needs_one_value() {
+        let (result, offset) = run(sep_by1(unsigned::<u32>(), tag(",")), "7");
+        assert_eq!(result.unwrap(), vec![7]);
+        assert_eq!(offset, 1);
+        assert_eq!(error_offset(sep_by1(unsigned::<u32>(), tag(",")), "  x"), 2);
+    }
+
+    #[test]
+    fn delimiters() {
+        let inner = || signed::<i32>();
+        assert_eq!(
+            run(delimited(tag("("), inner(), tag(")")), "(-4)")
+                .0
+                .unwrap(),
+            -4
+        );
+        assert_eq!(
+            error_offset(delimited(tag("("), inner(), tag(")")), "(-4]"),
+            3
+        );
+        assert_eq!(error_offset(preceded(tag("#"), inner()), "#x"), 1);
+        assert_eq!(error_offset(terminated(inner(), linebreak()), "5 6"), 2);
+    }
+
+    #[test]
+    fn spans() {
+        let (result, _) = run(spanned((tag("a"), tag("b"))), "  a b c");
+        let (_, span) = result.unwrap();
+        assert_eq!((span.offset(), span.as_str()), (2, "a b"));
+
+        let even = || {
+            try_map(unsigned::<u32>(), |value, span| {
+                if value % 2 == 0 {
+                    Ok(value)
+                } else {
+                    Err(span.error("an even number"))
+                }
+            })
+        };
+        assert_eq!(run(even(), "12").0.unwrap(), 12);
+        assert_eq!(error_offset(even(), " 13"), 1);
+    }
+
+    #[test]
+    fn regex_and_tags() {
+        let (result, offset) = run(regex("[a-z]+"), " abc1");
+        assert_eq!(result.unwrap().as_str(), "abc");
+        assert_eq!(offset, 4);
+        assert_eq!(error_offset(regex("[a-z]+"), " 1"), 1);
+        assert_eq!(error_offset(tag("ab"), " ax"), 1);
+    }
+
+    #[test]
+    fn parse_iter_stops_after_error() {
+        let scanner = Scanner::new("1 2 x 3", true);
+        let values = ParseIter::new(scanner, unsigned::<u32>()).collect::<Vec<_>>();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[2].as_ref().unwrap_err().offset(), 4);
+    }
+}
@@ -0,0 +1,275 @@
+//! Scanning input that is read from a stream as it is needed
+
+use std::io::{self, BufRead};
+
+use super::{Origin, Scanner, Token, TokenScanner};
+
+/// The buffer capacity that is kept even when the lines that needed more have been consumed
+const MIN_CAPACITY: usize = 4096;
+
+/// A scanner that reads its input a line at a time from a `BufRead`, for input too large to read
+/// into memory at once
+///
+/// Only the lines from the one holding the current position onward are kept, and every scan sees at
+/// least the whole line its token starts on. A scan that consumes everything read so far, or that
+/// looks for input at the end of it, is run again with another line, so tokens and parsers that
+/// run on past the end of a line see the input that follows it. A scan that fails part way through
+/// what has been read fails without reading any further, so a token that spans lines is only found
+/// if it matches up to the end of each line it covers. If reading fails, the
+/// input is treated as ending there and the error is kept for `io_error`.
+pub struct StreamScanner<R> {
+    reader: R,
+    buffer: String,
+    offset: usize,
+    origin: Origin,
+    ignore_whitespace: bool,
+    eof: bool,
+    io_error: Option<io::Error>,
+}
+
+impl<R: BufRead> StreamScanner<R> {
+    /// Create a new scanner reading from a stream
+    ///
+    /// If `ignore_whitespace` is true, the scanner will skip over whitespace before scanning for tokens.
+    pub fn new(reader: R, ignore_whitespace: bool) -> StreamScanner<R> {
+        StreamScanner {
+            reader,
+            buffer: String::new(),
+            offset: 0,
+            origin: Origin::default(),
+            ignore_whitespace,
+            eof: false,
+            io_error: None,
+        }
+    }
+
+    /// Return the current offset relative to the start of the whole input
+    pub fn offset(&self) -> usize {
+        self.origin.offset + self.offset
+    }
+
+    /// Return the error that ended reading early, if there was one
+    pub fn io_error(&self) -> Option<&io::Error> {
+        self.io_error.as_ref()
+    }
+
+    /// Take the error that ended reading early, if there was one
+    pub fn take_io_error(&mut self) -> Option<io::Error> {
+        self.io_error.take()
+    }
+
+    /// Drop the lines before the one holding the current position
+    fn compact(&mut self) {
+        let Some(end) = self.buffer[..self.offset].rfind('\n') else {
+            return;
+        };
+        let dropped = end + 1;
+        self.origin.offset += dropped;
+        self.origin.line += self.buffer[..dropped].matches('\n').count();
+        self.buffer.drain(..dropped);
+        self.offset -= dropped;
+
+        // Give back the space a long run of lines needed once it has been consumed
+        if self.buffer.capacity() > (4 * self.buffer.len()).max(MIN_CAPACITY) {
+            self.buffer
+                .shrink_to(MIN_CAPACITY.max(2 * self.buffer.len()));
+        }
+    }
+
+    /// Read another line onto the end of the buffer, returning false at the end of the input
+    fn read_line(&mut self) -> bool {
+        if self.eof {
+            return false;
+        }
+        match self.reader.read_line(&mut self.buffer) {
+            Ok(0) => self.eof = true,
+            Ok(_) => {}
+            Err(err) => {
+                self.eof = true;
+                self.io_error = Some(err);
+            }
+        }
+        !self.eof
+    }
+
+    /// Read until a whole line follows the start of the next token, or to the end of the input
+    fn fill(&mut self) {
+        self.compact();
+        loop {
+            let rest = &self.buffer[self.offset..];
+            let rest = if self.ignore_whitespace {
+                rest.trim_start()
+            } else {
+                rest
+            };
+            if rest.contains('\n') || !self.read_line() {
+                return;
+            }
+        }
+    }
+
+    /// Run `f` on the buffered input, reading more and running it again while it needs more of
+    /// the input, and return its result with the offset it stopped at
+    ///
+    /// `f` needs more input if it consumed everything read so far, or if it looked for input at
+    /// the end of it, whether or not it went on to succeed without it.
+    fn run<T>(&mut self, mut f: impl FnMut(&mut Scanner<'_>) -> T) -> (T, usize) {
+        self.fill();
+        loop {
+            let mut view = Scanner::with_origin(
+                &self.buffer,
+                self.offset,
+                self.ignore_whitespace,
+                self.origin,
+            );
+            let result = f(&mut view);
+            let end = view.offset();
+            let needs_input = end == self.buffer.len() || view.reached_end();
+            if !needs_input || !self.read_line() {
+                return (result, end);
+            }
+        }
+    }
+
+    /// Create a token for a range of the buffer
+    fn token(&self, (start, end): (usize, usize)) -> Token<'_> {
+        Token {
+            string: &self.buffer,
+            start,
+            end,
+            origin: self.origin,
+        }
+    }
+}
+
+impl<R: BufRead> TokenScanner for StreamScanner<R> {
+    fn scan_with<T>(&mut self, f: impl FnMut(&mut Scanner<'_>) -> T) -> T {
+        let (result, end) = self.run(f);
+        self.offset = end;
+        result
+    }
+
+    fn scan_token<E>(
+        &mut self,
+        f: impl for<'b> Fn(&mut Scanner<'b>) -> Result<Token<'b>, E>,
+    ) -> Result<Token<'_>, E> {
+        let (span, end) = self.run(|scanner| f(scanner).map(|token| token.span()));
+        self.offset = end;
+        Ok(self.token(span?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+    use crate::util::scanner::combinators::{sep_by1, signed, tag, Parser};
+
+    /// Stream `input` through a one-byte buffer, so that every token is read across refills
+    fn stream(input: &str, ignore_whitespace: bool) -> StreamScanner<BufReader<&[u8]>> {
+        StreamScanner::new(
+            BufReader::with_capacity(1, input.as_bytes()),
+            ignore_whitespace,
+        )
+    }
+
+    #[test]
+    fn token_across_lines() {
+        let mut scanner = stream("ab\ncd\nef", false);
+        let token = scanner
+            .try_scan_regex("ab\\n(cd)?")
+            .map(|token| token.as_str());
+        assert_eq!(token, Some("ab\ncd"));
+        assert_eq!(scanner.offset(), 5);
+        assert!(scanner.try_scan_linebreak().is_some());
+        assert_eq!(
+            scanner.try_scan_string("ef").map(|token| token.offset()),
+            Some(6)
+        );
+        assert!(scanner.is_finished());
+    }
+
+    #[test]
+    fn failed_token_leaves_offset() {
+        let mut scanner = stream("ab\ncd\n", false);
+        assert!(scanner.try_scan_regex("ab\\nx").is_none());
+        assert_eq!(scanner.offset(), 0);
+        assert!(scanner.try_scan_string("ab").is_some());
+    }
+
+    #[test]
+    fn failed_token_reads_no_further() {
+        let mut scanner = stream("ab\ncd\nef\n", false);
+        assert!(scanner.try_scan_regex("ab\\ncd").is_none());
+        assert_eq!(scanner.buffer, "ab\n");
+    }
+
+    #[test]
+    fn parser_that_looks_past_the_buffer() {
+        let mut scanner = stream("1,\n2,\n3\nx", true);
+        let values = scanner.scan_with(|scanner| sep_by1(signed::<i32>(), tag(",")).parse(scanner));
+        assert_eq!(values.unwrap(), vec![1, 2, 3]);
+        assert_eq!(scanner.offset(), 7);
+    }
+
+    #[test]
+    fn failed_probes_keep_the_buffer_bounded() {
+        let input = "12 34\n".repeat(100_000);
+        let mut scanner = StreamScanner::new(BufReader::new(input.as_bytes()), true);
+        let mut lines = 0;
+        while !scanner.is_finished() {
+            assert!(scanner.try_scan_string("x").is_none());
+            assert_eq!(scanner.scan_int::<u32>().unwrap(), 12);
+            assert!(scanner.try_scan_string("x").is_none());
+            assert_eq!(scanner.scan_int::<u32>().unwrap(), 34);
+            assert!(scanner.buffer.len() <= 12, "{:?}", scanner.buffer);
+            assert!(scanner.buffer.capacity() <= 2 * MIN_CAPACITY);
+            lines += 1;
+        }
+        assert_eq!(lines, 100_000);
+    }
+
+    #[test]
+    fn token_running_to_end_of_buffer() {
+        let mut scanner = stream("a b\nc d\n", false);
+        let token = scanner.try_scan_regex("[^x]*").map(|token| token.as_str());
+        assert_eq!(token, Some("a b\nc d\n"));
+        assert!(scanner.is_finished());
+    }
+
+    #[test]
+    fn matches_scanner() {
+        let input = "12 -7\n\n  345\n-6 7";
+        let mut streamed = stream(input, true);
+        let mut scanner = Scanner::new(input, true);
+        loop {
+            let expected = scanner.try_scan_int::<i32>().map(Result::unwrap);
+            assert_eq!(streamed.try_scan_int::<i32>().map(Result::unwrap), expected);
+            assert_eq!(streamed.offset(), scanner.offset());
+            if expected.is_none() {
+                break;
+            }
+        }
+        assert!(streamed.is_finished());
+    }
+
+    #[test]
+    fn errors_keep_their_position() {
+        let mut scanner = stream("1 2\n3 x\n", true);
+        for expected in 1..=3 {
+            assert_eq!(scanner.scan_int::<i32>().unwrap(), expected);
+        }
+        let error = scanner.scan_int::<i32>().unwrap_err();
+        assert_eq!((error.offset(), error.line(), error.column()), (6, 2, 3));
+    }
+
+    #[test]
+    fn scan_iter_over_lines() {
+        let values = stream("4\n5\n\n6\n", true)
+            .scan_iter::<i64>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, vec![4, 5, 6]);
+    }
+}
@@ -0,0 +1,293 @@
+//! Tracing what scanners match, for debugging parsers
+//!
+//! While a sink is installed with `with_sink`, every scanner on the thread reports each attempt to
+//! scan a token to it, including scanners created for sections, tokens and streams.
+
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    rc::Rc,
+};
+
+/// An attempt to scan a token
+#[derive(Clone, Debug)]
+pub struct ScanEvent<'a> {
+    /// What was scanned for, such as `string "Game"` or `regex '[LR]+'`
+    pub pattern: &'a str,
+    /// The offset in the whole input where the token starts, or where it was expected
+    pub start: usize,
+    /// The offset in the whole input just past the token, or `start` if nothing matched
+    pub end: usize,
+    /// The number of bytes of whitespace skipped before `start`
+    pub skipped: usize,
+    /// The matched text, which is empty if nothing matched
+    pub text: &'a str,
+    /// Whether the token was found
+    pub matched: bool,
+}
+
+impl std::fmt::Display for ScanEvent<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.matched {
+            write!(
+                f,
+                "{}..{}: matched {} {:?}",
+                self.start, self.end, self.pattern, self.text
+            )?;
+        } else {
+            write!(f, "{}: no {}", self.start, self.pattern)?;
+        }
+        if self.skipped > 0 {
+            let unit = if self.skipped == 1 { "byte" } else { "bytes" };
+            write!(f, " (after {} {} of whitespace)", self.skipped, unit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Something that receives scan events
+///
+/// Any `Fn(&ScanEvent)` closure is a sink.
+pub trait TraceSink {
+    /// Handle one scan event
+    fn event(&self, event: &ScanEvent<'_>);
+}
+
+impl<F: Fn(&ScanEvent<'_>)> TraceSink for F {
+    fn event(&self, event: &ScanEvent<'_>) {
+        self(event)
+    }
+}
+
+thread_local! {
+    static SINK: RefCell<Option<Rc<dyn TraceSink>>> = const { RefCell::new(None) };
+}
+
+/// Puts back the sink that was installed before `with_sink`, even if `f` panics
+struct Restore(Option<Rc<dyn TraceSink>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        SINK.with(|sink| *sink.borrow_mut() = self.0.take());
+    }
+}
+
+/// Run `f` with scan events on this thread sent to `sink`
+pub fn with_sink<T>(sink: Rc<dyn TraceSink>, f: impl FnOnce() -> T) -> T {
+    let _restore = Restore(SINK.with(|current| current.replace(Some(sink))));
+    f()
+}
+
+/// Return true if a sink is installed on this thread
+pub(super) fn is_active() -> bool {
+    SINK.with(|sink| sink.borrow().is_some())
+}
+
+/// Send an event to the sink installed on this thread, if there is one
+pub(super) fn emit(event: &ScanEvent<'_>) {
+    // Release the borrow before calling the sink, so that a sink may scan things itself
+    if let Some(sink) = SINK.with(|sink| sink.borrow().clone()) {
+        sink.event(event);
+    }
+}
+
+/// A sink that writes each event on a line of its own
+pub struct WriteSink<W: Write> {
+    writer: RefCell<W>,
+}
+
+impl<W: Write> WriteSink<W> {
+    /// Create a sink that writes to `writer`
+    pub fn new(writer: W) -> WriteSink<W> {
+        WriteSink {
+            writer: RefCell::new(writer),
+        }
+    }
+}
+
+impl WriteSink<io::Stderr> {
+    /// Create a sink that writes to standard error
+    pub fn stderr() -> WriteSink<io::Stderr> {
+        WriteSink::new(io::stderr())
+    }
+}
+
+impl WriteSink<BufWriter<File>> {
+    /// Create a sink that writes to a new file, replacing any file already at `path`
+    pub fn create(path: impl AsRef<Path>) -> io::Result<WriteSink<BufWriter<File>>> {
+        Ok(WriteSink::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> TraceSink for WriteSink<W> {
+    fn event(&self, event: &ScanEvent<'_>) {
+        // A trace is a debugging aid, so failing to write one should not fail the scan
+        let _ = writeln!(self.writer.borrow_mut(), "{}", event);
+    }
+}
+
+/// A sink that records the tokens that were consumed, to show them under the input with `render`
+///
+/// Tokens that contain other tokens, such as whole sections, are left out so that the tokens
+/// scanned from inside them stay visible.
+#[derive(Default)]
+pub struct AnnotatedInput {
+    spans: RefCell<Vec<(usize, usize)>>,
+}
+
+impl AnnotatedInput {
+    /// Create an empty annotation
+    pub fn new() -> AnnotatedInput {
+        AnnotatedInput::default()
+    }
+
+    /// Render the input with each consumed token marked on the line below it
+    ///
+    /// Each token is marked with a `^` under its first character and a `~` under the rest, and
+    /// lines where nothing was consumed are left unmarked:
+    ///
+    /// ```text
+    /// 1 | 32T3K 765
+    ///   | ^~~~~ ^~~
+    /// ```
+    pub fn render(&self, input: &str) -> String {
+        let mut spans = self.spans.borrow().clone();
+        spans.sort_by_key(|&(start, end)| (start, std::cmp::Reverse(end)));
+        spans.dedup();
+
+        let mut marks = vec![None; input.len()];
+        for (index, &(start, end)) in spans.iter().enumerate() {
+            let contains_next = spans
+                .get(index + 1)
+                .is_some_and(|&(next_start, next_end)| next_start < end && next_end <= end);
+            if contains_next {
+                continue;
+            }
+            let Some(span) = marks.get_mut(start..end.min(input.len())) else {
+                continue;
+            };
+            for (index, mark) in span.iter_mut().enumerate() {
+                *mark = Some(if index == 0 { '^' } else { '~' });
+            }
+        }
+
+        let width = input.lines().count().max(1).to_string().len();
+        let mut result = String::new();
+        let mut line_start = 0;
+        for (number, line) in input.split_inclusive('\n').enumerate() {
+            let text = line.trim_end_matches(['\r', '\n']);
+            result += &format!("{:>width$} | {}\n", number + 1, text);
+
+            // Keep tabs in the padding so that the marks line up with the text above them
+            let annotation = text
+                .char_indices()
+                .map(|(index, c)| match marks[line_start + index] {
+                    Some(mark) => mark,
+                    None if c == '\t' => '\t',
+                    None => ' ',
+                })
+                .collect::<String>();
+            let annotation = annotation.trim_end();
+            if !annotation.is_empty() {
+                result += &format!("{:width$} | {}\n", "", annotation);
+            }
+            line_start += line.len();
+        }
+        result
+    }
+}
+
+impl TraceSink for AnnotatedInput {
+    fn event(&self, event: &ScanEvent<'_>) {
+        if event.matched && event.end > event.start {
+            self.spans.borrow_mut().push((event.start, event.end));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::scanner::{Scanner, TokenScanner};
+
+    /// Run `f` with a `WriteSink` installed and return the lines it wrote
+    fn written(f: impl FnOnce()) -> Vec<String> {
+        let sink = Rc::new(WriteSink::new(Vec::new()));
+        with_sink(sink.clone(), f);
+        let output = String::from_utf8(sink.writer.borrow().clone()).unwrap();
+        output.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn write_sink_lines() {
+        let lines = written(|| {
+            let mut scanner = Scanner::new("Game  12: x", true);
+            scanner.scan_string("Game").unwrap();
+            scanner.scan_int::<u32>().unwrap();
+            assert!(scanner.try_scan_regex("[0-9]+").is_none());
+            assert!(scanner.peek_string(":").is_some());
+        });
+        assert_eq!(
+            lines,
+            [
+                "0..4: matched string \"Game\" \"Game\"",
+                "6..8: matched u32 in base 10 \"12\" (after 2 bytes of whitespace)",
+                "8: no regex '[0-9]+'",
+            ]
+        );
+    }
+
+    #[test]
+    fn sink_is_restored() {
+        let outer = written(|| {
+            let inner = written(|| {
+                Scanner::new("a", false).try_scan_string("a");
+            });
+            assert_eq!(inner, ["0..1: matched string \"a\" \"a\""]);
+            Scanner::new("b", false).try_scan_string("a");
+        });
+        assert_eq!(outer, ["0: no string \"a\""]);
+        assert!(!is_active());
+
+        let result = std::panic::catch_unwind(|| {
+            with_sink(Rc::new(|_: &ScanEvent<'_>| {}), || panic!("solver failed"))
+        });
+        assert!(result.is_err());
+        assert!(!is_active());
+    }
+
+    #[test]
+    fn annotated_input() {
+        let input = "32T3K 765\n\nKK677 28\n\tQQQJA 483\n\n# unscanned\n";
+        let annotated = Rc::new(AnnotatedInput::new());
+        with_sink(annotated.clone(), || {
+            let mut scanner = Scanner::new(input, true);
+            for _ in 0..2 {
+                let section = scanner.scan_section().unwrap();
+                let mut lines = section.scanner(true);
+                while !lines.is_finished() {
+                    lines.scan_regex(r"\w{5}").unwrap();
+                    lines.scan_int::<u32>().unwrap();
+                }
+            }
+        });
+        assert_eq!(
+            annotated.render(input),
+            [
+                "1 | 32T3K 765",
+                "  | ^~~~~ ^~~",
+                "2 | ",
+                "3 | KK677 28",
+                "  | ^~~~~ ^~",
+                "4 | \tQQQJA 483",
+                "  | \t^~~~~ ^~~",
+                "5 | ",
+                "6 | # unscanned",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,751 @@
+//! Graph searches over nodes given by successor functions
+//!
+//! Every search starts from any number of nodes at once. Traversals and connected components keep
+//! track of the nodes they have seen in a `Visited` set, which is a `HashSet` by default or a
+//! `GridVisited` bitmap for positions on a grid. Weighted searches take costs that are `Copy`,
+//! ordered and have a zero, such as the integer types.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::{BuildHasher, Hash},
+};
+
+use nalgebra_glm::IVec2;
+use num::Zero;
+
+/// A set of the nodes a search has seen
+pub trait Visited<N> {
+    /// Record a node, returning true if it had not been seen before
+    fn insert(&mut self, node: &N) -> bool;
+
+    /// Return true if a node has been seen
+    #[allow(dead_code)]
+    fn contains(&self, node: &N) -> bool;
+}
+
+impl<N: Hash + Eq + Clone, S: BuildHasher> Visited<N> for HashSet<N, S> {
+    fn insert(&mut self, node: &N) -> bool {
+        !HashSet::contains(self, node) && HashSet::insert(self, node.clone())
+    }
+
+    fn contains(&self, node: &N) -> bool {
+        HashSet::contains(self, node)
+    }
+}
+
+impl<N, V: Visited<N>> Visited<N> for &mut V {
+    fn insert(&mut self, node: &N) -> bool {
+        (**self).insert(node)
+    }
+
+    fn contains(&self, node: &N) -> bool {
+        (**self).contains(node)
+    }
+}
+
+/// A dense set of positions on a grid, one flag per cell
+///
+/// Positions outside the grid count as already seen, so searches using it never leave the grid.
+#[derive(Clone, Debug)]
+pub struct GridVisited {
+    size: IVec2,
+    cells: Vec<bool>,
+    len: usize,
+}
+
+impl GridVisited {
+    /// Create an empty set for a grid of the given width and height
+    pub fn new(size: IVec2) -> GridVisited {
+        GridVisited {
+            size,
+            cells: vec![false; (size.x.max(0) * size.y.max(0)) as usize],
+            len: 0,
+        }
+    }
+
+    /// Return the number of positions that have been seen
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return true if no position has been seen
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn index_of(&self, pos: IVec2) -> Option<usize> {
+        ((0..self.size.x).contains(&pos.x) && (0..self.size.y).contains(&pos.y))
+            .then(|| (pos.y * self.size.x + pos.x) as usize)
+    }
+}
+
+impl Visited<IVec2> for GridVisited {
+    fn insert(&mut self, pos: &IVec2) -> bool {
+        let Some(index) = self.index_of(*pos) else {
+            return false;
+        };
+        let new = !self.cells[index];
+        self.cells[index] = true;
+        self.len += usize::from(new);
+        new
+    }
+
+    fn contains(&self, pos: &IVec2) -> bool {
+        self.index_of(*pos).is_none_or(|index| self.cells[index])
+    }
+}
+
+/// An iterator over the nodes reachable from the starts, returned by `bfs` and `dfs`
+///
+/// Each node is yielded once, along with the number of steps it took to reach it. For a
+/// breadth-first traversal that is its distance from the nearest start.
+pub struct Traversal<N, F, V> {
+    frontier: VecDeque<(N, usize)>,
+    successors: F,
+    visited: V,
+    depth_first: bool,
+}
+
+impl<N, I, F, V> Traversal<N, F, V>
+where
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    V: Visited<N>,
+{
+    fn new(
+        starts: impl IntoIterator<Item = N>,
+        successors: F,
+        mut visited: V,
+        depth_first: bool,
+    ) -> Self {
+        let frontier = starts
+            .into_iter()
+            .filter(|start| visited.insert(start))
+            .map(|start| (start, 0))
+            .collect();
+        Traversal {
+            frontier,
+            successors,
+            visited,
+            depth_first,
+        }
+    }
+
+    /// Return the set of nodes seen so far
+    #[allow(dead_code)]
+    pub fn visited(&self) -> &V {
+        &self.visited
+    }
+
+    /// Visit every remaining node and return the set of all the nodes that were seen
+    pub fn finish(mut self) -> V {
+        while self.next().is_some() {}
+        self.visited
+    }
+}
+
+impl<N, I, F, V> Iterator for Traversal<N, F, V>
+where
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    V: Visited<N>,
+{
+    type Item = (N, usize);
+
+    fn next(&mut self) -> Option<(N, usize)> {
+        let (node, steps) = if self.depth_first {
+            self.frontier.pop_back()?
+        } else {
+            self.frontier.pop_front()?
+        };
+        for next in (self.successors)(&node) {
+            if self.visited.insert(&next) {
+                self.frontier.push_back((next, steps + 1));
+            }
+        }
+        Some((node, steps))
+    }
+}
+
+/// Traverse the nodes reachable from the starts in breadth-first order
+#[allow(dead_code)]
+pub fn bfs<N, I, F>(
+    starts: impl IntoIterator<Item = N>,
+    successors: F,
+) -> Traversal<N, F, HashSet<N>>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+{
+    bfs_with(starts, successors, HashSet::new())
+}
+
+/// Traverse the nodes reachable from the starts in breadth-first order, recording them in `visited`
+///
+/// Nodes already in `visited` are treated as walls, which lets several traversals share one set.
+#[allow(dead_code)]
+pub fn bfs_with<N, I, F, V>(
+    starts: impl IntoIterator<Item = N>,
+    successors: F,
+    visited: V,
+) -> Traversal<N, F, V>
+where
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    V: Visited<N>,
+{
+    Traversal::new(starts, successors, visited, false)
+}
+
+/// Traverse the nodes reachable from the starts in depth-first order
+#[allow(dead_code)]
+pub fn dfs<N, I, F>(
+    starts: impl IntoIterator<Item = N>,
+    successors: F,
+) -> Traversal<N, F, HashSet<N>>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+{
+    dfs_with(starts, successors, HashSet::new())
+}
+
+/// Traverse the nodes reachable from the starts in depth-first order, recording them in `visited`
+///
+/// Nodes already in `visited` are treated as walls, which lets several traversals share one set.
+pub fn dfs_with<N, I, F, V>(
+    starts: impl IntoIterator<Item = N>,
+    successors: F,
+    visited: V,
+) -> Traversal<N, F, V>
+where
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    V: Visited<N>,
+{
+    Traversal::new(starts, successors, visited, true)
+}
+
+/// A route found by a search, from a start to its last node
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct Path<N, C> {
+    /// The nodes along the route, including the start and the end
+    pub nodes: Vec<N>,
+    /// The total cost of the route
+    pub cost: C,
+}
+
+/// The cheapest known route to every node a search reached
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+pub struct Paths<N, C> {
+    /// The cost of each node, and the node it was reached from unless it is a start
+    nodes: HashMap<N, (C, Option<N>)>,
+}
+
+#[allow(dead_code)]
+impl<N: Hash + Eq + Clone, C: Copy> Paths<N, C> {
+    fn new() -> Self {
+        Paths {
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Return the number of nodes reached
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Return true if no node was reached
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Return true if a node was reached
+    pub fn contains(&self, node: &N) -> bool {
+        self.nodes.contains_key(node)
+    }
+
+    /// Return the cost of the cheapest route to a node, or None if it was not reached
+    pub fn cost(&self, node: &N) -> Option<C> {
+        self.nodes.get(node).map(|&(cost, _)| cost)
+    }
+
+    /// Iterate over the nodes reached along with their costs, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&N, C)> {
+        self.nodes.iter().map(|(node, &(cost, _))| (node, cost))
+    }
+
+    /// Return the cheapest route to a node, or None if it was not reached
+    pub fn path_to(&self, node: &N) -> Option<Path<N, C>> {
+        let cost = self.cost(node)?;
+        let mut nodes = vec![node.clone()];
+        while let Some((_, Some(previous))) = self.nodes.get(nodes.last()?) {
+            nodes.push(previous.clone());
+        }
+        nodes.reverse();
+        Some(Path { nodes, cost })
+    }
+}
+
+/// Search breadth-first until `goal` accepts a node, counting each step as a cost of 1
+#[allow(dead_code)]
+fn breadth_first<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> (Paths<N, usize>, Option<N>)
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut paths = Paths::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if !paths.contains(&start) {
+            paths.nodes.insert(start.clone(), (0, None));
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((node, steps)) = queue.pop_front() {
+        if goal(&node) {
+            return (paths, Some(node));
+        }
+        for next in successors(&node) {
+            if !paths.contains(&next) {
+                paths
+                    .nodes
+                    .insert(next.clone(), (steps + 1, Some(node.clone())));
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+    (paths, None)
+}
+
+/// Find the shortest route from any start to a node that `goal` accepts, counting steps
+#[allow(dead_code)]
+pub fn bfs_path<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, usize>>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let (paths, end) = breadth_first(starts, successors, goal);
+    paths.path_to(&end?)
+}
+
+/// Find the shortest route from the nearest start to every reachable node, counting steps
+#[allow(dead_code)]
+pub fn bfs_paths<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+) -> Paths<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    breadth_first(starts, successors, |_| false).0
+}
+
+/// Search in order of cost plus `heuristic` until `goal` accepts a node
+///
+/// With a heuristic that never overestimates the remaining cost, the first node accepted is
+/// reached by a cheapest route.
+#[allow(dead_code)]
+fn best_first<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut goal: impl FnMut(&N) -> bool,
+) -> (Paths<N, C>, Option<N>)
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Zero,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut paths = Paths::new();
+    // Nodes are kept out of the heap so that they need not be ordered; the heap holds their
+    // priority and their index in `queued`, along with the cost they were queued at
+    let mut queued = Vec::new();
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        if !paths.contains(&start) {
+            paths.nodes.insert(start.clone(), (C::zero(), None));
+            heap.push(Reverse((heuristic(&start), queued.len())));
+            queued.push((start, C::zero()));
+        }
+    }
+
+    while let Some(Reverse((_, index))) = heap.pop() {
+        let (node, cost) = queued[index].clone();
+        // Skip entries superseded by a cheaper route found after they were queued
+        if paths.cost(&node).is_some_and(|best| best < cost) {
+            continue;
+        }
+        if goal(&node) {
+            return (paths, Some(node));
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            if paths.cost(&next).is_some_and(|best| best <= next_cost) {
+                continue;
+            }
+            paths
+                .nodes
+                .insert(next.clone(), (next_cost, Some(node.clone())));
+            heap.push(Reverse((next_cost + heuristic(&next), queued.len())));
+            queued.push((next, next_cost));
+        }
+    }
+    (paths, None)
+}
+
+/// Find the cheapest route from any start to a node that `goal` accepts
+///
+/// Costs must not be negative.
+#[allow(dead_code)]
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Zero,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let (paths, end) = best_first(starts, successors, |_| C::zero(), goal);
+    paths.path_to(&end?)
+}
+
+/// Find the cheapest route from the nearest start to every reachable node
+///
+/// Costs must not be negative.
+#[allow(dead_code)]
+pub fn dijkstra_paths<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+) -> Paths<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Zero,
+    I: IntoIterator<Item = (N, C)>,
+{
+    best_first(starts, successors, |_| C::zero(), |_| false).0
+}
+
+/// Find the cheapest route from any start to a node that `goal` accepts, guided by an estimate of
+/// the cost remaining from each node
+///
+/// Costs must not be negative, and the route is only guaranteed to be the cheapest if `heuristic`
+/// never overestimates.
+#[allow(dead_code)]
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+    heuristic: impl FnMut(&N) -> C,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Zero,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let (paths, end) = best_first(starts, successors, heuristic, goal);
+    paths.path_to(&end?)
+}
+
+/// Split nodes into the groups that are connected to one another by `successors`
+///
+/// Each component lists its nodes in the order they were reached, and components come in the order
+/// of their first node in `nodes`. Successors should be symmetric, as in an undirected graph.
+#[allow(dead_code)]
+pub fn components<N, I>(
+    nodes: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+) -> Vec<Vec<N>>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    components_with(nodes, successors, HashSet::new())
+}
+
+/// Split nodes into the groups that are connected to one another, recording them in `visited`
+#[allow(dead_code)]
+pub fn components_with<N, I, V>(
+    nodes: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut visited: V,
+) -> Vec<Vec<N>>
+where
+    N: Clone,
+    I: IntoIterator<Item = N>,
+    V: Visited<N>,
+{
+    let mut components = Vec::new();
+    for node in nodes {
+        if visited.contains(&node) {
+            continue;
+        }
+        let component = bfs_with([node], &mut successors, &mut visited)
+            .map(|(node, _)| node)
+            .collect();
+        components.push(component);
+    }
+    components
+}
+
+/// Label each node with the index of its component in the result of `components`
+#[allow(dead_code)]
+pub fn component_labels<N, I>(
+    nodes: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+) -> HashMap<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    components(nodes, successors)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(label, component)| component.into_iter().map(move |node| (node, label)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small weighted graph, given as edges from each node
+    ///
+    /// `a` reaches `c` directly at a cost of 10 or through `b` at a cost of 3, so `c` is queued
+    /// twice by a cheapest-first search.
+    fn weighted(node: &char) -> Vec<(char, u32)> {
+        match node {
+            'a' => vec![('c', 10), ('b', 1)],
+            'b' => vec![('c', 2)],
+            'c' => vec![('d', 1)],
+            'e' => vec![('d', 1)],
+            _ => vec![],
+        }
+    }
+
+    /// The open cells of a small maze, where `#` is a wall
+    const MAZE: [&str; 5] = [
+        "S..#....", //
+        ".#.#.##.", //
+        ".#...#..", //
+        ".####.#.", //
+        "......#E", //
+    ];
+
+    fn find(c: u8) -> IVec2 {
+        MAZE.iter()
+            .enumerate()
+            .find_map(|(y, row)| {
+                row.bytes()
+                    .position(|b| b == c)
+                    .map(|x| IVec2::new(x as i32, y as i32))
+            })
+            .unwrap()
+    }
+
+    fn open_neighbours(pos: &IVec2) -> Vec<IVec2> {
+        [
+            IVec2::new(0, -1),
+            IVec2::new(1, 0),
+            IVec2::new(0, 1),
+            IVec2::new(-1, 0),
+        ]
+        .into_iter()
+        .map(|delta| pos + delta)
+        .filter(|next| {
+            MAZE.get(next.y as usize)
+                .and_then(|row| row.as_bytes().get(next.x as usize))
+                .is_some_and(|&b| next.x >= 0 && next.y >= 0 && b != b'#')
+        })
+        .collect()
+    }
+
+    /// Return the nodes from 0 to 9 that follow a node in a line, stepping by one or by three
+    fn line(node: &i32) -> Vec<i32> {
+        [node + 1, node + 3]
+            .into_iter()
+            .filter(|next| *next < 10)
+            .collect()
+    }
+
+    #[test]
+    fn bfs_counts_steps_from_nearest_start() {
+        let steps = bfs([0, 5], line).collect::<HashMap<_, _>>();
+        assert_eq!(steps.len(), 10);
+        assert_eq!(steps[&0], 0);
+        assert_eq!(steps[&5], 0);
+        assert_eq!(steps[&3], 1);
+        assert_eq!(steps[&4], 2);
+        assert_eq!(steps[&9], 2);
+    }
+
+    #[test]
+    fn traversals_skip_visited_nodes() {
+        let visited = HashSet::from([3]);
+        let reached = bfs_with([0], line, visited)
+            .map(|(node, _)| node)
+            .collect::<Vec<_>>();
+        assert_eq!(reached, vec![0, 1, 2, 4, 5, 7, 6, 8, 9]);
+
+        let order = dfs([0], line).map(|(node, _)| node).collect::<Vec<_>>();
+        assert_eq!(order[..3], [0, 3, 6]);
+        assert_eq!(dfs([0], line).finish().len(), 10);
+    }
+
+    #[test]
+    fn bfs_path_reconstructs_route() {
+        let path = bfs_path([0], line, |&node| node == 7).unwrap();
+        assert_eq!(path.cost, 3);
+        assert_eq!(path.nodes.first(), Some(&0));
+        assert_eq!(path.nodes.last(), Some(&7));
+        assert_eq!(path.nodes.len(), 4);
+        assert!(path
+            .nodes
+            .windows(2)
+            .all(|pair| line(&pair[0]).contains(&pair[1])));
+        assert!(bfs_path([0], line, |&node| node == 10).is_none());
+    }
+
+    #[test]
+    fn bfs_paths_from_several_starts() {
+        let paths = bfs_paths([find(b'S'), find(b'E')], open_neighbours);
+        assert_eq!(paths.cost(&IVec2::new(2, 2)), Some(4));
+        assert_eq!(paths.cost(&IVec2::new(7, 0)), Some(4));
+        let path = paths.path_to(&IVec2::new(7, 0)).unwrap();
+        assert_eq!(path.nodes.first(), Some(&find(b'E')));
+        assert_eq!(paths.path_to(&find(b'S')).unwrap().nodes, vec![find(b'S')]);
+        assert!(paths.path_to(&IVec2::new(3, 0)).is_none());
+    }
+
+    #[test]
+    fn dijkstra_takes_cheapest_route() {
+        let path = dijkstra(['a'], weighted, |&node| node == 'd').unwrap();
+        assert_eq!(path.nodes, vec!['a', 'b', 'c', 'd']);
+        assert_eq!(path.cost, 4);
+        assert!(dijkstra(['a'], weighted, |&node| node == 'e').is_none());
+    }
+
+    #[test]
+    fn dijkstra_skips_stale_entries() {
+        let mut expanded = Vec::new();
+        let paths = dijkstra_paths(['a'], |node| {
+            expanded.push(*node);
+            weighted(node)
+        });
+        expanded.sort();
+        assert_eq!(expanded, vec!['a', 'b', 'c', 'd']);
+        assert_eq!(paths.cost(&'c'), Some(3));
+        assert_eq!(paths.len(), 4);
+    }
+
+    #[test]
+    fn dijkstra_from_several_starts() {
+        let path = dijkstra(['a', 'e'], weighted, |&node| node == 'd').unwrap();
+        assert_eq!(path.nodes, vec!['e', 'd']);
+        assert_eq!(path.cost, 1);
+    }
+
+    #[test]
+    fn astar_matches_dijkstra() {
+        let (start, end) = (find(b'S'), find(b'E'));
+        let successors = |pos: &IVec2| open_neighbours(pos).into_iter().map(|next| (next, 1));
+        let manhattan = |pos: &IVec2| (end - pos).abs().sum();
+
+        let mut astar_expanded = 0;
+        let path = astar(
+            [start],
+            |pos| {
+                astar_expanded += 1;
+                successors(pos)
+            },
+            manhattan,
+            |&pos| pos == end,
+        )
+        .unwrap();
+        let mut dijkstra_expanded = 0;
+        let expected = dijkstra(
+            [start],
+            |pos| {
+                dijkstra_expanded += 1;
+                successors(pos)
+            },
+            |&pos| pos == end,
+        )
+        .unwrap();
+
+        assert_eq!(path.cost, 15);
+        assert_eq!(path.cost, expected.cost);
+        assert_eq!(path.nodes.len(), 16);
+        assert!(astar_expanded <= dijkstra_expanded);
+    }
+
+    #[test]
+    fn grid_visited_bounds() {
+        let mut visited = GridVisited::new(IVec2::new(3, 2));
+        assert!(visited.is_empty());
+        assert!(visited.insert(&IVec2::new(2, 1)));
+        assert!(!visited.insert(&IVec2::new(2, 1)));
+        for outside in [
+            IVec2::new(-1, 0),
+            IVec2::new(3, 0),
+            IVec2::new(0, 2),
+            IVec2::new(0, -1),
+        ] {
+            assert!(visited.contains(&outside));
+            assert!(!visited.insert(&outside));
+        }
+        assert_eq!(visited.len(), 1);
+
+        // A traversal with no bounds of its own stays on the grid
+        let all = |pos: &IVec2| {
+            [
+                IVec2::new(1, 0),
+                IVec2::new(-1, 0),
+                IVec2::new(0, 1),
+                IVec2::new(0, -1),
+            ]
+            .map(|delta| pos + delta)
+        };
+        let visited =
+            bfs_with([IVec2::new(0, 0)], all, GridVisited::new(IVec2::new(3, 2))).finish();
+        assert_eq!(visited.len(), 6);
+    }
+
+    #[test]
+    fn components_and_labels() {
+        let edges = |node: &i32| match node {
+            1 => vec![2],
+            2 => vec![1, 3],
+            3 => vec![2],
+            4 => vec![5],
+            5 => vec![4],
+            _ => vec![],
+        };
+        let groups = components([4, 1, 2, 6, 3, 5], edges);
+        assert_eq!(groups, vec![vec![4, 5], vec![1, 2, 3], vec![6]]);
+
+        let labels = component_labels(1..=6, edges);
+        assert_eq!(labels.len(), 6);
+        assert_eq!(labels[&1], labels[&3]);
+        assert_ne!(labels[&1], labels[&4]);
+        assert_eq!(labels[&6], 2);
+    }
+}
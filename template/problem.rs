@@ -1,18 +1,18 @@
 use crate::aoc::prelude::*;
 
-pub struct SolutionPart1;
-pub struct SolutionPart2;
+/// Solver for day {{day}}
+struct Solution;
 
-pub const SOLUTIONS: Solutions = (&SolutionPart1, &SolutionPart2);
+pub const SOLUTIONS: Solutions = Solution::SOLUTIONS;
 
-impl Aoc for SolutionPart1 {
-    fn solve(&self, input: &str) -> String {
-        todo!()
+impl Solver for Solution {
+    type Parsed = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Parsed, AocError> {
+        Ok(input.lines().map(str::to_string).collect())
     }
-}
 
-impl Aoc for SolutionPart2 {
-    fn solve(&self, input: &str) -> String {
-        todo!()
+    fn part1(lines: &Self::Parsed) -> Result<Answer, AocError> {
+        todo!("solve part 1 from {} lines", lines.len())
     }
 }